@@ -10,16 +10,13 @@ use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
 use rustyline::validate::Validator;
 use rustyline::Helper;
-use crate::roon::RoonClient;
+use crate::roon::{RoonClient, BrowseList, ConnectionMethod, ConnectionState, BROWSE_PAGE_SIZE};
 use crate::upnp;
-use crate::dcs;
+use crate::dcs::DcsClient;
 use simplelog::*;
 use colored::Colorize;
 use chrono::Local;
 
-/// Default hostname for dCS devices when not specified
-const DEFAULT_DCS_HOST: &str = "dcs-vivaldi.local";
-
 /// Command metadata with name and help text
 pub struct CommandInfo {
     pub name: &'static str,
@@ -36,38 +33,63 @@ pub fn get_command_definitions() -> Vec<CommandInfo> {
         CommandInfo { name: "exit", description: "Exit interactive mode", usage: None },
         CommandInfo { name: "verbose", description: "Toggle verbose logging or set level", usage: Some("[off|error|warn|info|debug|trace]") },
         CommandInfo { name: "version", description: "Show version information", usage: None },
+        CommandInfo { name: "config", description: "Show effective configuration and where each value came from", usage: Some("show") },
 
         // Roon commands
         CommandInfo { name: "status", description: "Show connection status", usage: None },
         CommandInfo { name: "reconnect", description: "Reconnect to Roon Core", usage: None },
         CommandInfo { name: "zones", description: "List available zones", usage: None },
+        CommandInfo { name: "outputs", description: "List outputs with volume and source controls", usage: None },
+        CommandInfo { name: "history", description: "Show tracks played, newest last", usage: Some("[zone] [--since YYYY-MM-DD]") },
+        CommandInfo { name: "stats", description: "Show listening statistics (default: last 7 days)", usage: Some("[zone] [--since DATE] [--until DATE] [--limit N] [--json]") },
         CommandInfo { name: "now-playing", description: "Show currently playing tracks", usage: None },
         CommandInfo { name: "queue", description: "Show queue for zone (defaults to first playing zone)", usage: Some("[zone]") },
-        CommandInfo { name: "play", description: "Start playback in zone", usage: Some("<zone_id>") },
-        CommandInfo { name: "pause", description: "Pause playback in zone", usage: Some("<zone_id>") },
-        CommandInfo { name: "stop", description: "Stop playback in zone", usage: Some("<zone_id>") },
-        CommandInfo { name: "mute", description: "Toggle mute for zone", usage: Some("<zone_id>") },
+        CommandInfo { name: "play", description: "Start playback in zone", usage: Some("<zone>") },
+        CommandInfo { name: "pause", description: "Pause playback in zone", usage: Some("<zone>") },
+        CommandInfo { name: "stop", description: "Stop playback in zone", usage: Some("<zone>") },
+        CommandInfo { name: "mute", description: "Toggle mute for zone", usage: Some("<zone>") },
+        CommandInfo { name: "volume", description: "Set or step volume for a zone or output", usage: Some("<zone> <level|+n|-n|up|down>") },
+        CommandInfo { name: "shuffle", description: "Turn shuffle on/off for zone (toggles if omitted)", usage: Some("<zone> [on|off]") },
+        CommandInfo { name: "loop", description: "Set loop mode for zone", usage: Some("<zone> <one|all|off>") },
+        CommandInfo { name: "radio", description: "Turn Roon Radio on/off for zone (toggles if omitted)", usage: Some("<zone> [on|off]") },
+        CommandInfo { name: "group", description: "Group outputs (or zones) into one zone", usage: Some("<zone> <zone> [zone...]") },
+        CommandInfo { name: "ungroup", description: "Split a grouped zone into its outputs", usage: Some("<zone>") },
+        CommandInfo { name: "transfer", description: "Move playback from one zone to another", usage: Some("<from> <to>") },
+        CommandInfo { name: "standby", description: "Put a zone's or output's source controls into standby", usage: Some("<zone> [control_key]") },
+        CommandInfo { name: "wake", description: "Wake a zone's or output's source controls (convenience switch)", usage: Some("<zone> [control_key]") },
+        CommandInfo { name: "browse", description: "Browse the library (path like Library/Albums, or albums, artists, playlists)", usage: Some("[path] | --item <item_key> | --offset <n>") },
+        CommandInfo { name: "search", description: "Search the library", usage: Some("<query>") },
+        CommandInfo { name: "play-item", description: "Play an item from the last browse/search", usage: Some("<zone> <item_key> [now|queue|next|radio]") },
+        CommandInfo { name: "play-album", description: "Find an album and play it in a zone", usage: Some("<album> --zone <zone> [--queue|--next|--radio]") },
+        CommandInfo { name: "play-track", description: "Find a track and play it in a zone", usage: Some("<track> --zone <zone> [--queue|--next|--radio]") },
+        CommandInfo { name: "play-playlist", description: "Find a playlist and play it in a zone", usage: Some("<playlist> --zone <zone> [--queue|--next]") },
+        CommandInfo { name: "play-radio", description: "Find a My Live Radio station and play it in a zone", usage: Some("<station> --zone <zone>") },
 
         // UPnP commands
         CommandInfo { name: "upnp-discover", description: "Discover all UPnP devices on network", usage: None },
         CommandInfo { name: "upnp-renderers", description: "Discover UPnP MediaRenderer devices", usage: None },
-        CommandInfo { name: "upnp-info", description: "Get detailed device information", usage: Some("<url>") },
-        CommandInfo { name: "upnp-xml", description: "Get raw device XML description", usage: Some("<url>") },
+        CommandInfo { name: "upnp-info", description: "Get detailed device information", usage: Some("<url|zone>") },
+        CommandInfo { name: "upnp-xml", description: "Get raw device XML description", usage: Some("<url|zone>") },
         CommandInfo { name: "upnp-service", description: "Get service description XML (SCPD)", usage: Some("<url> <service>") },
-        CommandInfo { name: "upnp-position", description: "Get current playback position and metadata", usage: Some("<url>") },
-        CommandInfo { name: "upnp-state", description: "Get current playback state (playing/paused/stopped)", usage: Some("<url>") },
-        CommandInfo { name: "upnp-playing", description: "Get comprehensive now playing info (state, track, format)", usage: Some("<url>") },
+        CommandInfo { name: "upnp-position", description: "Get current playback position and metadata", usage: Some("<url|zone>") },
+        CommandInfo { name: "upnp-state", description: "Get current playback state (playing/paused/stopped)", usage: Some("<url|zone>") },
+        CommandInfo { name: "upnp-playing", description: "Get comprehensive now playing info (state, track, format)", usage: Some("<url|zone>") },
 
         // dCS API commands
-        CommandInfo { name: "dcs-playing", description: "Get current playback info (track, artist, album, format)", usage: Some("<host>") },
-        CommandInfo { name: "dcs-format", description: "Get current audio format (sample rate, bit depth, input)", usage: Some("<host>") },
-        CommandInfo { name: "dcs-settings", description: "Get device settings (display, sync mode)", usage: Some("<host>") },
-        CommandInfo { name: "dcs-upsampler", description: "Get upsampler settings (output rate, filter)", usage: Some("<host>") },
-        CommandInfo { name: "dcs-inputs", description: "Get current and available digital inputs", usage: Some("<host>") },
-        CommandInfo { name: "dcs-playmode", description: "Get current play mode (Network, USB, etc)", usage: Some("<host>") },
-        CommandInfo { name: "dcs-menu", description: "Get available menu options for device", usage: Some("<host>") },
-        CommandInfo { name: "dcs-set-brightness", description: "Set display brightness (0-4)", usage: Some("<host> <level>") },
-        CommandInfo { name: "dcs-set-display", description: "Set display mode (on/off)", usage: Some("<host> <on|off>") },
+        CommandInfo { name: "dcs-playing", description: "Get current playback info (track, artist, album, format)", usage: Some("[host|zone]") },
+        CommandInfo { name: "dcs-format", description: "Get current audio format (sample rate, bit depth, input)", usage: Some("[host|zone]") },
+        CommandInfo { name: "dcs-settings", description: "Get device settings (display, sync mode)", usage: Some("[host|zone]") },
+        CommandInfo { name: "dcs-upsampler", description: "Get upsampler settings (output rate, filter)", usage: Some("[host|zone]") },
+        CommandInfo { name: "dcs-inputs", description: "Get current and available digital inputs", usage: Some("[host|zone]") },
+        CommandInfo { name: "dcs-playmode", description: "Get current play mode (Network, USB, etc)", usage: Some("[host|zone]") },
+        CommandInfo { name: "dcs-menu", description: "Get available menu options for device", usage: Some("[host|zone] <path>") },
+        CommandInfo { name: "dcs-set-brightness", description: "Set display brightness (0-4)", usage: Some("[host|zone] <level>") },
+        CommandInfo { name: "dcs-set-display", description: "Set display mode (on/off)", usage: Some("[host|zone] <on|off>") },
+        CommandInfo { name: "dcs-set-input", description: "Select a digital input (see dcs-inputs)", usage: Some("[host|zone] <input>") },
+        CommandInfo { name: "dcs-set-playmode", description: "Set play mode (see dcs-playmode)", usage: Some("[host|zone] <mode>") },
+        CommandInfo { name: "dcs-volume", description: "Show or set volume and balance in dB (experimental)", usage: Some("[host|zone] [dB|up|down|balance <dB>]") },
+        CommandInfo { name: "dcs-mute", description: "Mute, unmute or toggle mute (experimental)", usage: Some("[host|zone] [on|off]") },
+        CommandInfo { name: "dcs-phase", description: "Show or set absolute phase (experimental)", usage: Some("[host|zone] [normal|inverted]") },
     ]
 }
 
@@ -79,7 +101,7 @@ struct CommandCompleter {
 impl CommandCompleter {
     fn new(include_roon_commands: bool) -> Self {
         let definitions = get_command_definitions();
-        let roon_commands = ["status", "reconnect", "zones", "now-playing", "queue", "play", "pause", "stop", "mute"];
+        let roon_commands = ["status", "reconnect", "zones", "outputs", "now-playing", "queue", "play", "pause", "stop", "mute", "volume", "shuffle", "loop", "radio", "group", "ungroup", "transfer", "standby", "wake", "browse", "search", "play-item", "play-album", "play-track", "play-playlist", "play-radio"];
 
         let commands: Vec<String> = definitions
             .iter()
@@ -130,6 +152,9 @@ impl Validator for CommandCompleter {}
 
 impl Helper for CommandCompleter {}
 
+/// Plays shown by `history` without --since
+const HISTORY_DEFAULT_COUNT: usize = 25;
+
 /// Format duration in mm:ss format
 fn format_duration(seconds: u32) -> String {
     let mins = seconds / 60;
@@ -137,16 +162,234 @@ fn format_duration(seconds: u32) -> String {
     format!("{}:{:02}", mins, secs)
 }
 
-/// Get the path to the history file
-fn get_history_file_path() -> Option<PathBuf> {
-    if let Some(home_dir) = dirs::home_dir() {
-        let history_path = home_dir.join(".roon-rd_history");
-        Some(history_path)
+/// Write listening statistics as tables, with a weekday/hour heatmap
+fn write_stats(out: &OutputDest, stats: &crate::stats::ListeningStats) {
+    let since = stats.since.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    let until = stats.until.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    out.writeln("".to_string());
+    out.writeln(format!("  {} {} to {}{}",
+        "Listening".bold(), since, until,
+        stats.zone.as_ref().map(|z| format!(" in {}", z)).unwrap_or_default()));
+    out.writeln(format!("  {} plays, {:.1} hours", stats.plays, stats.listening_hours));
+    if stats.plays == 0 {
+        out.writeln("".to_string());
+        return;
+    }
+
+    let top_lists = [("Top artists", &stats.top_artists), ("Top albums", &stats.top_albums), ("Top tracks", &stats.top_tracks)];
+    for (title, entries) in top_lists {
+        out.writeln("".to_string());
+        out.writeln(format!("  {}", title.bold()));
+        for (i, entry) in entries.iter().enumerate() {
+            let artist = entry.artist.as_ref().map(|a| format!(" - {}", a).dimmed().to_string()).unwrap_or_default();
+            out.writeln(format!("  {:>3}. {}{}  {}", i + 1, entry.name, artist,
+                format!("{} plays, {} min", entry.plays, entry.minutes).dimmed()));
+        }
+    }
+
+    out.writeln("".to_string());
+    out.writeln(format!("  {}", "Zones".bold()));
+    for zone in &stats.zones {
+        out.writeln(format!("  {:<24} {:>6.1} h  {}", zone.zone_name, zone.hours, format!("{} plays", zone.plays).dimmed()));
+    }
+
+    out.writeln("".to_string());
+    out.writeln(format!("  {}", "Days".bold()));
+    let max_day = stats.days.iter().map(|d| d.minutes).max().unwrap_or(0).max(1);
+    for day in &stats.days {
+        let bar = "█".repeat(((day.minutes * 30) / max_day) as usize);
+        out.writeln(format!("  {}  {:>5} min  {}", day.date, day.minutes, bar.cyan()));
+    }
+
+    // Weekday x hour heatmap, shaded relative to the busiest hour
+    out.writeln("".to_string());
+    out.writeln(format!("  {}", "Heatmap (minutes by weekday and hour)".bold()));
+    out.writeln(format!("       {}", (0..24).map(|h| format!("{:<3}", h)).collect::<String>().trim_end()));
+    let shades = [" ", "░", "▒", "▓", "█"];
+    let max_cell = stats.heatmap.iter().flatten().copied().max().unwrap_or(0).max(1);
+    for (day, hours) in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"].iter().zip(&stats.heatmap) {
+        let row: String = hours.iter()
+            .map(|&minutes| {
+                let shade = if minutes == 0 { 0 } else { 1 + ((minutes * 3) / max_cell) as usize };
+                shades[shade.min(4)].repeat(2) + " "
+            })
+            .collect();
+        out.writeln(format!("  {}  {}", day, row.trim_end().cyan()));
+    }
+    out.writeln("".to_string());
+}
+
+/// Write a page of browse results with item keys for drilling down
+fn write_browse_list(out: &OutputDest, list: &BrowseList) {
+    out.writeln("".to_string());
+    if list.items.is_empty() {
+        out.writeln(format!("  {} (empty)", list.title));
     } else {
-        None
+        out.writeln(format!("  {} ({}-{} of {})", list.title, list.offset + 1, list.offset + list.items.len(), list.count));
+    }
+    if let Some(subtitle) = &list.subtitle {
+        out.writeln(format!("  {}", subtitle));
+    }
+    out.writeln("  ─────────────────────────────────────".to_string());
+    out.writeln("".to_string());
+
+    for (i, item) in list.items.iter().enumerate() {
+        out.writeln(format!("  {:4}. {}", list.offset + i + 1, item.title));
+        if let Some(subtitle) = &item.subtitle {
+            if !subtitle.is_empty() {
+                out.writeln(format!("        {}", subtitle));
+            }
+        }
+        if let Some(item_key) = &item.item_key {
+            let hint = item.hint.as_deref().unwrap_or("item");
+            out.writeln(format!("        [{}] {}", hint, item_key).dimmed().to_string());
+        }
+    }
+
+    if list.offset + list.items.len() < list.count {
+        out.writeln("".to_string());
+        out.writeln(format!("  More: browse --offset {}", list.offset + list.items.len()));
+    }
+    out.writeln("".to_string());
+}
+
+/// Write the volume, mute, balance and phase of a dCS device
+fn write_dcs_volume(out: &OutputDest, settings: &crate::dcs::DcsVolumeSettings) {
+    out.writeln("  Volume Settings:".to_string());
+    out.writeln("".to_string());
+
+    if let Some(volume) = settings.volume_db {
+        out.writeln(format!("    Volume: {:.1} dB", volume));
+    }
+    if let Some(mute) = settings.mute {
+        out.writeln(format!("    Mute: {}", if mute { "on" } else { "off" }));
+    }
+    if let Some(balance) = settings.balance_db {
+        let side = if balance < 0.0 { " (left)" } else if balance > 0.0 { " (right)" } else { "" };
+        out.writeln(format!("    Balance: {:.1} dB{}", balance, side));
+    }
+    if let Some(inverted) = settings.phase_inverted {
+        out.writeln(format!("    Phase: {}", if inverted { "inverted" } else { "normal" }));
+    }
+
+    out.writeln("".to_string());
+}
+
+/// Address of a device argument: a zone mapped to a device of `kind` ("dcs" or "upnp") gives
+/// the device's host or location, anything else is used as given
+async fn device_address(client: Option<&RoonClient>, arg: &str, kind: &str) -> String {
+    if let Some(client) = client {
+        if let Ok(Some(device)) = client.get_zone_device(arg).await {
+            if device.kind() == kind {
+                let address = device.dcs_host().or(device.upnp_location()).unwrap_or(arg);
+                return address.to_string();
+            }
+        }
+    }
+    arg.to_string()
+}
+
+/// Whether a leading dcs-* argument names a device rather than a value: a zone mapped to a
+/// dCS device, a configured dCS host, or anything that looks like a host name or address
+async fn is_dcs_device(client: Option<&RoonClient>, arg: &str) -> bool {
+    if arg.contains('.') || arg.contains(':') {
+        return true;
+    }
+    let settings = match client {
+        Some(client) => client.get_settings().await,
+        None => RoonClient::load_settings(),
+    };
+    if settings.dcs_hosts().iter().any(|host| host.eq_ignore_ascii_case(arg)) {
+        return true;
+    }
+    match client {
+        Some(client) => matches!(client.get_zone_device(arg).await, Ok(Some(device)) if device.dcs_host().is_some()),
+        None => false,
     }
 }
 
+/// Zone filter for history and stats (see RoonClient::history_zone_filter); the raw name without a connection
+async fn history_zone_filter(client: Option<&RoonClient>, zone: Option<String>) -> (Option<String>, Option<String>) {
+    match (client, zone) {
+        (Some(client), Some(zone)) => {
+            let (zone, error) = client.history_zone_filter(&zone).await;
+            (Some(zone), error)
+        }
+        (_, zone) => (zone, None),
+    }
+}
+
+/// Split arguments on whitespace, keeping "double quoted words" together (quotes removed)
+fn split_args(args: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    for c in args.chars() {
+        if c == '"' {
+            quoted = !quoted;
+            in_word = true;
+        } else if c.is_whitespace() && !quoted {
+            if in_word {
+                words.push(std::mem::take(&mut word));
+                in_word = false;
+            }
+        } else {
+            word.push(c);
+            in_word = true;
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Zone argument rule shared by the zone commands: the zone is every word before the command's
+/// `values` trailing values, so names need no quotes (`volume Living Room 40`)
+/// Returns (zone, values), None if there are no words left for the zone
+fn zone_and_values(args: &[String], values: usize) -> Option<(String, &[String])> {
+    if args.len() <= values {
+        return None;
+    }
+    let (zone, values) = args.split_at(args.len() - values);
+    Some((zone.join(" "), values))
+}
+
+/// Parse `<name> --zone <zone> [--now|--queue|--next|--radio]` for the play-* commands
+/// Names may be quoted; quotes are stripped since shell arguments arrive re-joined
+/// Returns (name, zone, action)
+fn parse_play_args(arg: &str) -> Result<(String, String, &'static str), String> {
+    let mut name: Vec<&str> = Vec::new();
+    let mut zone: Vec<&str> = Vec::new();
+    let mut action = "now";
+    let mut in_zone = false;
+
+    for token in arg.split_whitespace() {
+        match token {
+            "--zone" => in_zone = true,
+            "--now" => { action = "now"; in_zone = false; }
+            "--queue" => { action = "queue"; in_zone = false; }
+            "--next" => { action = "next"; in_zone = false; }
+            "--radio" => { action = "radio"; in_zone = false; }
+            _ if in_zone => zone.push(token),
+            _ => name.push(token),
+        }
+    }
+
+    let strip = |parts: Vec<&str>| parts.join(" ").trim_matches(|c| c == '"' || c == '\'').to_string();
+    let (name, zone) = (strip(name), strip(zone));
+    if name.is_empty() || zone.is_empty() {
+        return Err("missing name or --zone".to_string());
+    }
+    Ok((name, zone, action))
+}
+
+/// Get the path to the history file
+fn get_history_file_path() -> Option<PathBuf> {
+    Some(crate::config::history_path())
+}
+
 /// Load command history from file
 fn load_history() -> Vec<String> {
     if let Some(history_path) = get_history_file_path() {
@@ -231,10 +474,18 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                 if let Some(name) = core_name {
                     out.writeln(format!("  Core:   {}", name));
                 }
+                match client.get_connection_method() {
+                    Some(ConnectionMethod::Direct(address)) => out.writeln(format!("  Via:    direct ({})", address)),
+                    Some(ConnectionMethod::Discovery) => out.writeln("  Via:    discovery".to_string()),
+                    None => {}
+                }
             } else {
-                out.writeln("  Status: Not connected".to_string());
-                out.writeln("".to_string());
-                out.writeln("  Authorize the extension in Roon Settings > Extensions".to_string());
+                let state = client.get_connection_state().await;
+                out.writeln(format!("  Status: {}", state));
+                if matches!(state, ConnectionState::AwaitingAuthorization | ConnectionState::Disconnected) {
+                    out.writeln("".to_string());
+                    out.writeln("  Authorize the extension in Roon Settings > Extensions".to_string());
+                }
             }
             out.writeln("".to_string());
             Ok(())
@@ -253,17 +504,27 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                 }
                 out.writeln("".to_string());
             } else {
+                let zone_keys = client.get_zone_keys().await;
+                let settings = client.get_settings().await;
                 out.writeln("".to_string());
                 for zone in &zones {
                     // Zone name with state
                     let state_str = format!("{:?}", zone.state).to_lowercase();
                     out.writeln(format!("  {} ({})", zone.display_name, state_str));
                     out.writeln(format!("    ID: {}", zone.zone_id));
+                    if let Some(key) = zone_keys.get(&zone.zone_id) {
+                        out.writeln(format!("    Key: {}", key));
+                    }
+                    if let Some(device) = settings.device_for_zone(zone) {
+                        out.writeln(format!("    Device: {}", device));
+                    }
 
-                    // Show outputs (devices in this zone) indented
-                    for output in &zone.outputs {
-                        if output.display_name != zone.display_name {
-                            out.writeln(format!("    └─ {}", output.display_name));
+                    // Show outputs (devices in this zone) indented, with IDs for grouping
+                    for output in crate::roon::zone_outputs(zone) {
+                        out.writeln(format!("    └─ {} ({})", output.display_name, output.output_id));
+                        for sc in &output.source_controls {
+                            let standby = if sc.supports_standby { "" } else { ", no standby" };
+                            out.writeln(format!("         source: {} [{}{}] ({})", sc.display_name, sc.status, standby, sc.control_key));
                         }
                     }
                 }
@@ -271,6 +532,45 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
             }
             Ok(())
         }
+        "outputs" => {
+            let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+            let outputs = client.get_outputs().await;
+
+            out.writeln("".to_string());
+            if outputs.is_empty() {
+                out.writeln("  No outputs found.".to_string());
+                if !client.is_connected().await {
+                    out.writeln("  Not connected to Roon Core. Please authorize the extension.".to_string());
+                }
+            } else {
+                for output in &outputs {
+                    out.writeln(format!("  {}", output.display_name));
+                    out.writeln(format!("    ID: {}", output.output_id));
+                    out.writeln(format!("    Zone: {}", output.zone_id));
+                    match &output.volume {
+                        Some(volume) => {
+                            let value = volume.value.map(|v| v.to_string()).unwrap_or("-".to_string());
+                            let range = match (volume.min, volume.max) {
+                                (Some(min), Some(max)) => format!(" ({} to {})", min, max),
+                                _ => String::new(),
+                            };
+                            let muted = if volume.is_muted == Some(true) { ", muted" } else { "" };
+                            out.writeln(format!("    Volume: {} {}{}{}", value, volume.scale, range, muted));
+                        }
+                        None => out.writeln("    Volume: fixed".to_string()),
+                    }
+                    for sc in &output.source_controls {
+                        let standby = if sc.supports_standby { "" } else { ", no standby" };
+                        out.writeln(format!("    Source: {} [{}{}] ({})", sc.display_name, sc.status, standby, sc.control_key));
+                    }
+                    if !output.can_group_with_output_ids.is_empty() {
+                        out.writeln(format!("    Groups with: {} outputs", output.can_group_with_output_ids.len()));
+                    }
+                }
+            }
+            out.writeln("".to_string());
+            Ok(())
+        }
         "now-playing" => {
             let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
             let zones = client.get_zones().await;
@@ -324,6 +624,12 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                             out.writeln(format!("    {} {} / {}", bar, format_duration(position), format_duration(length)));
                         }
 
+                        // Zone settings
+                        out.writeln(format!("    Shuffle: {}  Loop: {}  Radio: {}",
+                                            if zone.settings.shuffle { "on" } else { "off" },
+                                            crate::roon::loop_mode_name(&zone.settings.repeat),
+                                            if zone.settings.auto_radio { "on" } else { "off" }));
+
                         out.writeln("".to_string());
                     }
                 }
@@ -355,8 +661,8 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
             // If a specific zone is specified, show queue for that zone
             // Otherwise show queue for the first zone with now_playing
             let target_zone = if parts.len() > 1 {
-                let zone_name = parts[1..].join(" ");
-                zones.iter().find(|z| z.display_name.to_lowercase().contains(&zone_name.to_lowercase()))
+                let zone_id = client.resolve_zone(&parts[1..].join(" ")).await?;
+                zones.iter().find(|z| z.zone_id == zone_id)
             } else {
                 zones.iter().find(|z| z.now_playing.is_some())
             };
@@ -367,7 +673,7 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                 out.writeln("  ─────────────────────────────────────".to_string());
                 out.writeln("".to_string());
 
-                // Subscribe to queue and wait for data
+                // Make sure the queue is subscribed (normally already live)
                 client.subscribe_to_queue(&zone.zone_id).await;
 
                 // Get the queue
@@ -499,8 +805,8 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
             out.writeln("".to_string());
 
             // Group commands by category
-            let general_cmds = ["help", "quit", "exit", "verbose", "version"];
-            let roon_cmds = ["status", "reconnect", "zones", "now-playing", "queue", "play", "pause", "stop", "mute"];
+            let general_cmds = ["help", "quit", "exit", "verbose", "version", "config", "history", "stats"];
+            let roon_cmds = ["status", "reconnect", "zones", "outputs", "now-playing", "queue", "play", "pause", "stop", "mute", "volume", "shuffle", "loop", "radio", "group", "ungroup", "transfer", "standby", "wake", "browse", "search", "play-item", "play-album", "play-track", "play-playlist", "play-radio"];
             let upnp_cmds: Vec<_> = definitions.iter().filter(|c| c.name.starts_with("upnp-")).collect();
             let dcs_cmds: Vec<_> = definitions.iter().filter(|c| c.name.starts_with("dcs-")).collect();
 
@@ -554,6 +860,19 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                     String::new()
                 };
 
+                // upnp-* commands also take a zone mapped to a UPnP renderer
+                let arg = if command.starts_with("upnp-") && !arg.is_empty() {
+                    device_address(client, &arg, "upnp").await
+                } else {
+                    arg
+                };
+
+                // dCS host from the extension settings, used by dcs-* commands when no host is given
+                let default_dcs_host = match client {
+                    Some(client) => client.get_settings().await.dcs_host,
+                    None => RoonClient::load_settings().dcs_host,
+                };
+
                 match command {
                     "upnp-info" => {
                         out.writeln("".to_string());
@@ -780,14 +1099,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let host = if parts.len() >= 2 {
                             parts[1]
                         } else {
-                            DEFAULT_DCS_HOST
+                            default_dcs_host.as_str()
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Getting playback info from {}...", host));
                         out.writeln("".to_string());
 
-                        match dcs::get_playback_info(host).await {
+                        match DcsClient::new(host).get_playback_info().await {
                             Ok(info) => {
                                 // Show playback state
                                 if let Some(state) = &info.state {
@@ -847,14 +1167,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let host = if parts.len() >= 2 {
                             parts[1]
                         } else {
-                            DEFAULT_DCS_HOST
+                            default_dcs_host.as_str()
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Getting audio format from {}...", host));
                         out.writeln("".to_string());
 
-                        match dcs::get_audio_format(host).await {
+                        match DcsClient::new(host).get_audio_format().await {
                             Ok(format) => {
                                 out.writeln("  Current Audio Format:".to_string());
                                 out.writeln("".to_string());
@@ -884,14 +1205,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let host = if parts.len() >= 2 {
                             parts[1]
                         } else {
-                            DEFAULT_DCS_HOST
+                            default_dcs_host.as_str()
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Getting device settings from {}...", host));
                         out.writeln("".to_string());
 
-                        match dcs::get_device_settings(host).await {
+                        match DcsClient::new(host).get_device_settings().await {
                             Ok(settings) => {
                                 out.writeln("  Device Settings:".to_string());
                                 out.writeln("".to_string());
@@ -920,14 +1242,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let host = if parts.len() >= 2 {
                             parts[1]
                         } else {
-                            DEFAULT_DCS_HOST
+                            default_dcs_host.as_str()
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Getting upsampler settings from {}...", host));
                         out.writeln("".to_string());
 
-                        match dcs::get_upsampler_settings(host).await {
+                        match DcsClient::new(host).get_upsampler_settings().await {
                             Ok(settings) => {
                                 out.writeln("  Upsampler Settings:".to_string());
                                 out.writeln("".to_string());
@@ -965,14 +1288,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let host = if parts.len() >= 2 {
                             parts[1]
                         } else {
-                            DEFAULT_DCS_HOST
+                            default_dcs_host.as_str()
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Getting digital inputs from {}...", host));
                         out.writeln("".to_string());
 
-                        match dcs::get_input_info(host).await {
+                        match DcsClient::new(host).get_input_info().await {
                             Ok(info) => {
                                 if let Some(current) = &info.current_input {
                                     out.writeln(format!("  Current Input: {}", current));
@@ -980,6 +1304,9 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                                 }
 
                                 out.writeln("  Available Inputs:".to_string());
+                                if info.available_inputs.is_empty() {
+                                    out.writeln("    (device did not list its inputs)".to_string());
+                                }
                                 for input in &info.available_inputs {
                                     let marker = if Some(input) == info.current_input.as_ref() { " *" } else { "" };
                                     out.writeln(format!("    - {}{}", input, marker));
@@ -999,14 +1326,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let host = if parts.len() >= 2 {
                             parts[1]
                         } else {
-                            DEFAULT_DCS_HOST
+                            default_dcs_host.as_str()
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Getting play mode from {}...", host));
                         out.writeln("".to_string());
 
-                        match dcs::get_play_mode(host).await {
+                        match DcsClient::new(host).get_play_mode().await {
                             Ok(mode_info) => {
                                 if let Some(mode) = &mode_info.mode {
                                     out.writeln(format!("  Play Mode: {}", mode));
@@ -1032,14 +1360,15 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let (host, path) = if parts.len() >= 3 {
                             (parts[1], parts[2])
                         } else {
-                            (DEFAULT_DCS_HOST, parts[1])
+                            (default_dcs_host.as_str(), parts[1])
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         out.writeln("".to_string());
                         out.writeln(format!("  Browsing menu: {}...", path));
                         out.writeln("".to_string());
 
-                        match dcs::get_menu(host, path).await {
+                        match DcsClient::new(host).get_menu(path).await {
                             Ok(menu) => {
                                 out.writeln(format!("  Menu: {}", menu.title));
                                 out.writeln(format!("  Path: {}", menu.path));
@@ -1092,8 +1421,9 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let (host, brightness_str) = if parts.len() >= 3 {
                             (parts[1], parts[2])
                         } else {
-                            (DEFAULT_DCS_HOST, parts[1])
+                            (default_dcs_host.as_str(), parts[1])
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         // Parse brightness value
                         let brightness: i32 = brightness_str.parse()
@@ -1103,7 +1433,7 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         out.writeln(format!("  Setting display brightness to {}...", brightness));
                         out.writeln("".to_string());
 
-                        match dcs::set_display_brightness(host, brightness).await {
+                        match DcsClient::new(host).set_display_brightness(brightness).await {
                             Ok(_) => {
                                 out.writeln("  ✓ Display brightness updated successfully".to_string());
                                 out.writeln("".to_string());
@@ -1124,8 +1454,9 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         let (host, state_str) = if parts.len() >= 3 {
                             (parts[1], parts[2].to_lowercase())
                         } else {
-                            (DEFAULT_DCS_HOST, parts[1].to_lowercase())
+                            (default_dcs_host.as_str(), parts[1].to_lowercase())
                         };
+                        let host = &device_address(client, host, "dcs").await;
 
                         // Parse on/off state
                         let display_off = match state_str.as_str() {
@@ -1138,7 +1469,7 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                         out.writeln(format!("  Turning display {}...", if display_off { "off" } else { "on" }));
                         out.writeln("".to_string());
 
-                        match dcs::set_display_off(host, display_off).await {
+                        match DcsClient::new(host).set_display_off(display_off).await {
                             Ok(_) => {
                                 out.writeln(format!("  ✓ Display turned {} successfully", if display_off { "off" } else { "on" }));
                                 out.writeln("".to_string());
@@ -1147,20 +1478,692 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                             Err(e) => return Err(format!("Failed to set display: {}", e))
                         }
                     }
+                    "dcs-set-input" => {
+                        // Select a dCS digital input
+                        // Usage: dcs-set-input [host] <input>
+                        let parts: Vec<&str> = query_type.split_whitespace().collect();
+
+                        if parts.len() < 2 {
+                            return Err("Usage: dcs-set-input [host] <input>\n\nExamples:\n  dcs-set-input AES1\n  dcs-set-input USB Audio\n  dcs-set-input dcs-vivaldi.local Network\n\nUse 'dcs-inputs' to see available inputs.".to_string());
+                        }
+
+                        // A leading host or zone is only taken as one if it names a dCS device,
+                        // so inputs with spaces work on the default host
+                        let (host, input) = if parts.len() >= 3 && is_dcs_device(client, parts[1]).await {
+                            (parts[1], parts[2..].join(" "))
+                        } else {
+                            (default_dcs_host.as_str(), parts[1..].join(" "))
+                        };
+                        let host = &device_address(client, host, "dcs").await;
+
+                        out.writeln("".to_string());
+                        out.writeln(format!("  Selecting input {}...", input));
+                        out.writeln("".to_string());
+
+                        match DcsClient::new(host).set_input(&input).await {
+                            Ok(_) => {
+                                if let Some(client) = client {
+                                    client.refresh_dcs_device(host).await;
+                                }
+                                out.writeln(format!("  ✓ Input set to {}", input));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Failed to set input: {}", e))
+                        }
+                    }
+                    "dcs-set-playmode" => {
+                        // Set dCS play mode
+                        // Usage: dcs-set-playmode [host] <mode>
+                        let parts: Vec<&str> = query_type.split_whitespace().collect();
+
+                        if parts.len() < 2 {
+                            return Err("Usage: dcs-set-playmode [host] <mode>\n\nExamples:\n  dcs-set-playmode Network\n  dcs-set-playmode dcs-vivaldi.local USB\n\nUse 'dcs-playmode' to see the current mode.".to_string());
+                        }
+
+                        // A leading host or zone is only taken as one if it names a dCS device,
+                        // so modes with spaces work on the default host
+                        let (host, mode) = if parts.len() >= 3 && is_dcs_device(client, parts[1]).await {
+                            (parts[1], parts[2..].join(" "))
+                        } else {
+                            (default_dcs_host.as_str(), parts[1..].join(" "))
+                        };
+                        let host = &device_address(client, host, "dcs").await;
+
+                        out.writeln("".to_string());
+                        out.writeln(format!("  Setting play mode to {}...", mode));
+                        out.writeln("".to_string());
+
+                        match DcsClient::new(host).set_play_mode(&mode).await {
+                            Ok(_) => {
+                                if let Some(client) = client {
+                                    client.refresh_dcs_device(host).await;
+                                }
+                                out.writeln(format!("  ✓ Play mode set to {}", mode));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Failed to set play mode: {}", e))
+                        }
+                    }
+                    "dcs-volume" => {
+                        // Show or set dCS volume and balance (experimental, see dcs::VOLUME_PATH)
+                        // Usage: dcs-volume [host] [dB|up|down|balance <dB>]
+                        let parts: Vec<&str> = query_type.split_whitespace().collect();
+                        let usage = "Usage: dcs-volume [host] [dB|up|down|balance <dB>]\n\nExamples:\n  dcs-volume\n  dcs-volume -30\n  dcs-volume dcs-vivaldi.local up\n  dcs-volume balance -1.5\n\nExperimental: the volume settings paths are unverified and may not work on your device.";
+
+                        // A leading argument that isn't a level or keyword is the host (or zone)
+                        let mut args = &parts[1..];
+                        let is_value = |arg: &str| arg.parse::<f64>().is_ok() || matches!(arg, "up" | "down" | "balance");
+                        let host = match args.first() {
+                            Some(arg) if !is_value(arg) => {
+                                args = &args[1..];
+                                *arg
+                            }
+                            _ => default_dcs_host.as_str(),
+                        };
+                        let host = &device_address(client, host, "dcs").await;
+                        let dcs = DcsClient::new(host);
+
+                        let result = match args {
+                            [] => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  Getting volume settings from {}...", host));
+                                out.writeln("".to_string());
+
+                                match dcs.get_volume_settings().await {
+                                    Ok(settings) => {
+                                        write_dcs_volume(&out, &settings);
+                                        return Ok(());
+                                    }
+                                    Err(e) => return Err(format!("Failed to get volume settings: {}", e))
+                                }
+                            }
+                            ["balance", level] => {
+                                let balance: f64 = level.parse()
+                                    .map_err(|_| format!("Invalid balance '{}'. Must be a number of dB.", level))?;
+                                dcs.set_balance(balance).await.map(|_| format!("Balance set to {:.1} dB", balance))
+                            }
+                            [step @ ("up" | "down")] => {
+                                let current = dcs.get_volume_settings().await
+                                    .map_err(|e| format!("Failed to get volume settings: {}", e))?
+                                    .volume_db
+                                    .ok_or("Device did not report its volume")?;
+                                let delta = if *step == "up" { 1.0 } else { -1.0 };
+                                let volume = (current + delta).clamp(crate::dcs::MIN_VOLUME_DB, crate::dcs::MAX_VOLUME_DB);
+                                dcs.set_volume(volume).await.map(|_| format!("Volume set to {:.1} dB", volume))
+                            }
+                            [level] => {
+                                let volume: f64 = level.parse().map_err(|_| usage.to_string())?;
+                                dcs.set_volume(volume).await.map(|_| format!("Volume set to {:.1} dB", volume))
+                            }
+                            _ => return Err(usage.to_string()),
+                        };
+
+                        match result {
+                            Ok(message) => {
+                                if let Some(client) = client {
+                                    client.refresh_dcs_device(host).await;
+                                }
+                                out.writeln("".to_string());
+                                out.writeln(format!("  ✓ {}", message));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Failed to set volume: {}", e))
+                        }
+                    }
+                    "dcs-mute" => {
+                        // Mute, unmute or toggle mute on a dCS device (experimental)
+                        // Usage: dcs-mute [host] [on|off]
+                        let parts: Vec<&str> = query_type.split_whitespace().collect();
+
+                        let (host, state_str) = match parts.len() {
+                            1 => (default_dcs_host.as_str(), None),
+                            2 if matches!(parts[1].to_lowercase().as_str(), "on" | "off") => (default_dcs_host.as_str(), Some(parts[1].to_lowercase())),
+                            2 => (parts[1], None),
+                            _ => (parts[1], Some(parts[2].to_lowercase())),
+                        };
+                        let host = &device_address(client, host, "dcs").await;
+                        let dcs = DcsClient::new(host);
+
+                        let mute = match state_str.as_deref() {
+                            Some("on") => true,
+                            Some("off") => false,
+                            Some(other) => return Err(format!("Invalid mute state '{}'. Must be 'on' or 'off'.", other)),
+                            None => {
+                                // No state given: toggle
+                                let current = dcs.get_volume_settings().await
+                                    .map_err(|e| format!("Failed to get mute state: {}", e))?
+                                    .mute
+                                    .ok_or("Device did not report its mute state")?;
+                                !current
+                            }
+                        };
+
+                        match dcs.set_mute(mute).await {
+                            Ok(_) => {
+                                if let Some(client) = client {
+                                    client.refresh_dcs_device(host).await;
+                                }
+                                out.writeln("".to_string());
+                                out.writeln(format!("  ✓ {}", if mute { "Muted" } else { "Unmuted" }));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Failed to set mute: {}", e))
+                        }
+                    }
+                    "dcs-phase" => {
+                        // Show or set absolute phase on a dCS device (experimental)
+                        // Usage: dcs-phase [host] [normal|inverted]
+                        let parts: Vec<&str> = query_type.split_whitespace().collect();
+
+                        let (host, phase_str) = match parts.len() {
+                            1 => (default_dcs_host.as_str(), None),
+                            2 if matches!(parts[1].to_lowercase().as_str(), "normal" | "inverted") => (default_dcs_host.as_str(), Some(parts[1].to_lowercase())),
+                            2 => (parts[1], None),
+                            _ => (parts[1], Some(parts[2].to_lowercase())),
+                        };
+                        let host = &device_address(client, host, "dcs").await;
+                        let dcs = DcsClient::new(host);
+
+                        let inverted = match phase_str.as_deref() {
+                            Some("inverted") => true,
+                            Some("normal") => false,
+                            Some(other) => return Err(format!("Invalid phase '{}'. Must be 'normal' or 'inverted'.", other)),
+                            None => {
+                                match dcs.get_volume_settings().await {
+                                    Ok(settings) => {
+                                        match settings.phase_inverted {
+                                            Some(inverted) => {
+                                                out.writeln("".to_string());
+                                                out.writeln(format!("  Phase: {}", if inverted { "inverted" } else { "normal" }));
+                                                out.writeln("".to_string());
+                                                return Ok(());
+                                            }
+                                            None => return Err("Device did not report its phase".to_string()),
+                                        }
+                                    }
+                                    Err(e) => return Err(format!("Failed to get phase: {}", e))
+                                }
+                            }
+                        };
+
+                        match dcs.set_phase(inverted).await {
+                            Ok(_) => {
+                                if let Some(client) = client {
+                                    client.refresh_dcs_device(host).await;
+                                }
+                                out.writeln("".to_string());
+                                out.writeln(format!("  ✓ Phase set to {}", if inverted { "inverted" } else { "normal" }));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Failed to set phase: {}", e))
+                        }
+                    }
+                    "volume" => {
+                        // Change volume for a zone (all outputs) or a single output
+                        // Usage: volume <zone> <level|+n|-n|up|down>
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let args = split_args(&arg);
+                        let Some((zone, values)) = zone_and_values(&args, 1) else {
+                            return Err("Usage: volume <zone> <level|+n|-n|up|down>\n\nExamples:\n  volume <zone> 40     (absolute)\n  volume <zone> +5     (relative)\n  volume <zone> down   (one step)\n\nUse 'zones' to see available zone IDs.".to_string());
+                        };
+                        let level = values[0].as_str();
+
+                        let (how, value) = match level {
+                            "up" => ("relative_step", 1),
+                            "down" => ("relative_step", -1),
+                            _ => {
+                                let value: i32 = level.parse()
+                                    .map_err(|_| format!("Invalid volume '{}'. Use a number, +n/-n, up or down.", level))?;
+                                if level.starts_with('+') || level.starts_with('-') {
+                                    ("relative", value)
+                                } else {
+                                    ("absolute", value)
+                                }
+                            }
+                        };
+
+                        match client.change_volume(&zone, how, value).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  Volume change ({} {}) sent", how, value));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Volume change failed: {}", e))
+                        }
+                    }
+                    "shuffle" | "radio" => {
+                        // Turn shuffle or Roon Radio on/off, toggling when no state is given
+                        // Usage: shuffle <zone> [on|off], radio <zone> [on|off]
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        // A trailing on/off is the state, the rest is the zone
+                        let args = split_args(&arg);
+                        let has_state = args.len() >= 2
+                            && matches!(args[args.len() - 1].to_lowercase().as_str(), "on" | "off");
+                        let Some((zone, values)) = zone_and_values(&args, if has_state { 1 } else { 0 }) else {
+                            return Err(format!("Usage: {} <zone> [on|off]\nUse 'zones' to see available zone IDs.", command));
+                        };
+
+                        let zone_id = client.resolve_zone(&zone).await?;
+                        let zone_id = zone_id.as_str();
+                        let zone = client.get_zones().await.into_iter()
+                            .find(|z| z.zone_id == zone_id)
+                            .ok_or_else(|| format!("Zone '{}' not found.", zone_id))?;
+
+                        let current = if command == "shuffle" { zone.settings.shuffle } else { zone.settings.auto_radio };
+                        let enabled = match values.first() {
+                            Some(state) => state.eq_ignore_ascii_case("on"),
+                            None => !current,
+                        };
+
+                        let result = if command == "shuffle" {
+                            client.change_zone_settings(zone_id, Some(enabled), None, None).await
+                        } else {
+                            client.change_zone_settings(zone_id, None, None, Some(enabled)).await
+                        };
+
+                        match result {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  {} turned {} for {}",
+                                                    if command == "shuffle" { "Shuffle" } else { "Roon Radio" },
+                                                    if enabled { "on" } else { "off" },
+                                                    zone.display_name));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Settings change failed: {}", e))
+                        }
+                    }
+                    "loop" => {
+                        // Set loop mode for a zone
+                        // Usage: loop <zone> <one|all|off>
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let args = split_args(&arg);
+                        let Some((zone, values)) = zone_and_values(&args, 1) else {
+                            return Err("Usage: loop <zone> <one|all|off>\nUse 'zones' to see available zone IDs.".to_string());
+                        };
+
+                        let zone_id = client.resolve_zone(&zone).await?;
+                        let mode = values[0].to_lowercase();
+
+                        match client.change_zone_settings(&zone_id, None, Some(&mode), None).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  Loop set to {}", mode));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Settings change failed: {}", e))
+                        }
+                    }
+                    "group" => {
+                        // Group outputs into one zone
+                        // Usage: group <zone> <zone> [zone...] (outputs or zones, names quoted if they have spaces)
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let ids = split_args(&arg);
+                        if ids.len() < 2 {
+                            return Err("Usage: group <zone> <zone> [zone...]\n\nZones may be output IDs, zone IDs or zone names (quote names with spaces). Use 'zones' to see them.".to_string());
+                        }
+
+                        match client.group_outputs(&ids).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  Group command sent for {} ids", ids.len()));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Group failed: {}", e))
+                        }
+                    }
+                    "ungroup" => {
+                        // Split a grouped zone back into individual outputs
+                        // Usage: ungroup <zone>
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let args = split_args(&arg);
+                        let Some((zone, _)) = zone_and_values(&args, 0) else {
+                            return Err("Usage: ungroup <zone>\nUse 'zones' to see available zone IDs.".to_string());
+                        };
+
+                        match client.ungroup_outputs(&zone).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln("  Ungroup command sent".to_string());
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Ungroup failed: {}", e))
+                        }
+                    }
+                    "transfer" => {
+                        // Move playback from one zone to another
+                        // Usage: transfer <from_zone> <to_zone>
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let args = split_args(&arg);
+                        if args.len() < 2 {
+                            return Err("Usage: transfer <from_zone> <to_zone>\nUse 'zones' to see available zone IDs.".to_string());
+                        }
+
+                        // Both zones may be several words: use the one split where both resolve
+                        let mut splits = Vec::new();
+                        for i in 1..args.len() {
+                            let (from, to) = args.split_at(i);
+                            if let (Ok(from), Ok(to)) = (client.resolve_zone(&from.join(" ")).await, client.resolve_zone(&to.join(" ")).await) {
+                                splits.push((from, to));
+                            }
+                        }
+                        let (from_zone_id, to_zone_id) = match splits.len() {
+                            1 => splits.remove(0),
+                            0 if args.len() == 2 => (client.resolve_zone(&args[0]).await?, client.resolve_zone(&args[1]).await?),
+                            0 => return Err("Could not find two zones in the arguments. Quote zone names, e.g. transfer \"Living Room\" Kitchen".to_string()),
+                            _ => return Err("The zones are ambiguous. Quote zone names, e.g. transfer \"Living Room\" Kitchen".to_string()),
+                        };
+                        match client.transfer_zone(&from_zone_id, &to_zone_id).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln("  Transfer command sent".to_string());
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Transfer failed: {}", e))
+                        }
+                    }
+                    "standby" | "wake" => {
+                        // Standby or wake the source controls of a zone or output
+                        // Usage: standby <zone> [control_key] / wake <zone> [control_key]
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let args = split_args(&arg);
+                        if args.is_empty() {
+                            return Err(format!("Usage: {} <zone> [control_key]\nUse 'zones' to see outputs and their source controls.", command));
+                        }
+
+                        // A trailing word is a control key unless the whole argument names a zone or output
+                        let whole = args.join(" ");
+                        let (zone, control_key) = if args.len() >= 2 && client.resolve_zone_or_output(&whole).await.is_err() {
+                            let (zone, values) = zone_and_values(&args, 1).ok_or("Missing zone")?;
+                            (zone, Some(values[0].as_str()))
+                        } else {
+                            (whole, None)
+                        };
+
+                        let result = if command == "standby" {
+                            client.standby(&zone, control_key).await
+                        } else {
+                            client.wake(&zone, control_key).await
+                        };
+
+                        match result {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  {} command sent", if command == "standby" { "Standby" } else { "Wake" }));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("{} failed: {}", command, e))
+                        }
+                    }
+                    "history" => {
+                        // Show the play history log
+                        // Usage: history [zone] [--since YYYY-MM-DD]
+                        let since_pos = parts.iter().position(|p| *p == "--since");
+                        let zone_parts = &parts[1..since_pos.unwrap_or(parts.len())];
+                        let zone = if zone_parts.is_empty() { None } else { Some(zone_parts.join(" ")) };
+                        let since = match since_pos {
+                            Some(pos) => {
+                                let since = parts.get(pos + 1).ok_or("Usage: history [zone] [--since YYYY-MM-DD]")?;
+                                Some(crate::history::parse_since(since)?)
+                            }
+                            None => None,
+                        };
+
+                        let (zone, zone_error) = history_zone_filter(client, zone).await;
+                        let records = crate::history::read_history(zone.as_deref(), since)?;
+                        if let (true, Some(e)) = (records.is_empty(), zone_error) {
+                            return Err(e);
+                        }
+                        out.writeln("".to_string());
+                        if records.is_empty() {
+                            out.writeln("  No plays recorded".to_string());
+                            out.writeln("".to_string());
+                            return Ok(());
+                        }
+
+                        // Without --since, just the most recent plays
+                        let shown = if since.is_none() {
+                            &records[records.len().saturating_sub(HISTORY_DEFAULT_COUNT)..]
+                        } else {
+                            &records[..]
+                        };
+                        if shown.len() < records.len() {
+                            out.writeln(format!("  ... {} earlier plays (use --since to see them)", records.len() - shown.len()).dimmed().to_string());
+                        }
+                        for record in shown {
+                            let started = record.started_at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+                            let artist = record.artist.as_deref().map(|a| format!(" - {}", a)).unwrap_or_default();
+                            out.writeln(format!("  {}  {:<16} {}{} {}",
+                                started,
+                                record.zone_name,
+                                record.track.bold(),
+                                artist,
+                                format!("({})", format_duration(record.played_secs as u32)).dimmed()));
+                        }
+                        out.writeln("".to_string());
+                        return Ok(());
+                    }
+                    "stats" => {
+                        // Show listening statistics from the play history
+                        // Usage: stats [zone] [--since DATE] [--until DATE] [--limit N] [--json]
+                        let usage = "Usage: stats [zone] [--since DATE] [--until DATE] [--limit N] [--json]";
+                        let mut zone_parts = Vec::new();
+                        let (mut since, mut until, mut limit, mut json) = (None, None, crate::stats::DEFAULT_TOP_COUNT, false);
+                        let mut args = parts[1..].iter();
+                        while let Some(arg) = args.next() {
+                            match *arg {
+                                "--since" => since = Some(crate::history::parse_since(args.next().ok_or(usage)?)?),
+                                "--until" => until = Some(crate::history::parse_until(args.next().ok_or(usage)?)?),
+                                "--limit" => limit = args.next().and_then(|n| n.parse().ok()).ok_or(usage)?,
+                                "--json" => json = true,
+                                _ => zone_parts.push(*arg),
+                            }
+                        }
+                        let zone = if zone_parts.is_empty() { None } else { Some(zone_parts.join(" ")) };
+
+                        let (zone, zone_error) = history_zone_filter(client, zone).await;
+                        let stats = crate::stats::compute(zone.as_deref(), since, until, limit)?;
+                        if let (0, Some(e)) = (stats.plays, zone_error) {
+                            return Err(e);
+                        }
+                        if json {
+                            let json = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+                            out.writeln(json);
+                            return Ok(());
+                        }
+                        write_stats(&out, &stats);
+                        return Ok(());
+                    }
+                    "config" => {
+                        // Show effective configuration
+                        // Usage: config show
+                        if parts.len() > 1 && parts[1] != "show" {
+                            return Err("Usage: config show".to_string());
+                        }
+
+                        let config = crate::config::get();
+                        let mut entries: Vec<(String, String, String)> = config.entries().into_iter()
+                            .map(|(key, value)| {
+                                let source = config.source(&key).to_string();
+                                (key, value, source)
+                            })
+                            .collect();
+
+                        // Values saved from Roon Settings > Extensions override the file (but not flags),
+                        // except the core address, which the Roon setting only supplies when neither sets it
+                        if let Some(client) = client {
+                            for (key, value) in client.get_settings().await.config_entries() {
+                                if key == "roon.core" && config.roon.core.is_some() {
+                                    continue;
+                                }
+                                match entries.iter_mut().find(|(k, _, _)| *k == key) {
+                                    Some(entry) if entry.1 != value && config.source(&key) != crate::config::ConfigSource::Flag => {
+                                        entry.1 = value;
+                                        entry.2 = crate::config::ConfigSource::RoonSettings.to_string();
+                                    }
+                                    Some(_) => {}
+                                    None => entries.push((key, value, crate::config::ConfigSource::RoonSettings.to_string())),
+                                }
+                            }
+                        }
+
+                        out.writeln("".to_string());
+                        match &config.path {
+                            Some(path) => out.writeln(format!("  Config file: {}", path.display())),
+                            None => out.writeln(format!("  Config file: {} (not found, using defaults)", crate::config::config_file_path().display())),
+                        }
+                        out.writeln(format!("  State dir:   {}", crate::config::state_dir().display()));
+                        out.writeln("".to_string());
+
+                        let width = entries.iter().map(|(key, _, _)| key.len()).max().unwrap_or(0);
+                        for (key, value, source) in &entries {
+                            let value = if value.is_empty() { "(none)".to_string() } else { value.clone() };
+                            out.writeln(format!("  {:<width$}  {:<24} {}", key, value, format!("[{}]", source).dimmed(), width = width));
+                        }
+                        out.writeln("".to_string());
+                        return Ok(());
+                    }
+                    "browse" => {
+                        // Browse the library hierarchy
+                        // Usage: browse [path] | browse --item <item_key> | browse --offset <n>
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let result = match parts.get(1) {
+                            Some(&"--item") => {
+                                let item_key = parts.get(2)
+                                    .ok_or("Usage: browse --item <item_key>")?;
+                                client.browse(None, Some(*item_key)).await
+                            }
+                            Some(&"--offset") => {
+                                let offset: usize = parts.get(2)
+                                    .and_then(|o| o.parse().ok())
+                                    .ok_or("Usage: browse --offset <n>")?;
+                                client.load(offset, BROWSE_PAGE_SIZE).await
+                            }
+                            _ => client.browse(Some(&arg), None).await,
+                        };
+
+                        match result {
+                            Ok(list) => {
+                                write_browse_list(&out, &list);
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Browse failed: {}", e))
+                        }
+                    }
+                    "search" => {
+                        // Search the library
+                        // Usage: search <query>
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        if arg.is_empty() {
+                            return Err("Usage: search <query>".to_string());
+                        }
+
+                        match client.search(&arg).await {
+                            Ok(list) => {
+                                write_browse_list(&out, &list);
+                                out.writeln("  Use 'browse --item <item_key>' to open a result.".to_string());
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Search failed: {}", e))
+                        }
+                    }
+                    "play-item" => {
+                        // Play an item_key from the most recent browse or search
+                        // Usage: play-item <zone> <item_key> [now|queue|next|radio]
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        // The item key and optional action come last, the rest is the zone
+                        let args = split_args(&arg);
+                        let has_action = args.last().map_or(false, |last| crate::roon::play_action_titles(last).is_some());
+                        let Some((zone, values)) = zone_and_values(&args, if has_action { 2 } else { 1 }) else {
+                            return Err("Usage: play-item <zone> <item_key> [now|queue|next|radio]\nUse 'browse' or 'search' to find item keys.".to_string());
+                        };
+
+                        let zone_id = client.resolve_zone(&zone).await?;
+                        let item_key = values[0].as_str();
+                        let action = values.get(1).map(|action| action.as_str()).unwrap_or("now");
+
+                        match client.play_item(&zone_id, item_key, action).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  Play ({}) command sent", action));
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Play failed: {}", e))
+                        }
+                    }
+                    "play-album" | "play-track" | "play-playlist" | "play-radio" => {
+                        // Find a library item by name and play it
+                        // Usage: play-album "Kind of Blue" --zone "Living Room" [--queue|--next|--radio]
+                        let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
+
+                        let kind = match command {
+                            "play-album" => "Albums",
+                            "play-track" => "Tracks",
+                            "play-playlist" => "Playlists",
+                            _ => "Radio",
+                        };
+
+                        let (name, zone, action) = parse_play_args(&arg)
+                            .map_err(|e| format!("{}\nUsage: {} <name> --zone <zone> [--queue|--next|--radio]", e, command))?;
+                        let zone_id = client.resolve_zone(&zone).await?;
+
+                        let item = client.find_library_item(kind, &name).await
+                            .map_err(|e| format!("Play failed: {}", e))?
+                            .ok_or(format!("Play failed: no {} found matching '{}'", kind.to_lowercase(), name))?;
+                        let item_key = item.item_key.clone().ok_or("Play failed: item has no key")?;
+
+                        match client.play_item(&zone_id, &item_key, action).await {
+                            Ok(_) => {
+                                out.writeln("".to_string());
+                                out.writeln(format!("  {} ({}) in {}", item.title, action, zone));
+                                if let Some(subtitle) = &item.subtitle {
+                                    out.writeln(format!("  {}", subtitle));
+                                }
+                                out.writeln("".to_string());
+                                return Ok(());
+                            }
+                            Err(e) => return Err(format!("Play failed: {}", e))
+                        }
+                    }
                     _ => {}
                 }
             }
 
-            // Check if it's a control command with zone_id
+            // Check if it's a control command with a zone (id, name, alias or prefix)
             let parts: Vec<&str> = query_type.split_whitespace().collect();
-            if parts.len() == 2 {
+            if parts.len() >= 2 {
                 let command = parts[0];
-                let zone_id = parts[1];
+                let zone = split_args(&parts[1..].join(" ")).join(" ");
 
                 match command {
                     "play" | "pause" | "stop" => {
                         let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
-                        match client.control_zone(zone_id, command).await {
+                        let zone_id = client.resolve_zone(&zone).await?;
+                        match client.control_zone(&zone_id, command).await {
                             Ok(_) => {
                                 out.writeln("".to_string());
                                 out.writeln(format!("  {} command sent to zone", command));
@@ -1173,7 +2176,7 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                     "mute" => {
                         let client = client.ok_or("Roon commands require connection. Remove --upnp-only flag.")?;
                         // Toggle mute - we'll use true to mute (server handler will toggle)
-                        match client.mute_output(zone_id, true).await {
+                        match client.mute_output(&zone, true).await {
                             Ok(_) => {
                                 out.writeln("".to_string());
                                 out.writeln("  Mute toggled for zone".to_string());
@@ -1186,7 +2189,7 @@ async fn execute_query_with_dest(client: Option<&RoonClient>, query_type: &str,
                     _ => Err(format!("Unknown command: {}\nType 'help' for available commands.", query_type))
                 }
             } else if parts.len() == 1 && (parts[0] == "play" || parts[0] == "pause" || parts[0] == "stop" || parts[0] == "mute") {
-                Err(format!("Usage: {} <zone_id>\nUse 'zones' to see available zone IDs.", parts[0]))
+                Err(format!("Usage: {} <zone>\nA zone is an ID, name, alias or unique name prefix. Use 'zones' to see them.", parts[0]))
             } else {
                 Err(format!("Unknown command: {}\nType 'help' for available commands.", query_type))
             }
@@ -1585,6 +2588,9 @@ pub async fn handle_tui(client: Option<Arc<Mutex<RoonClient>>>, verbose_flag: bo
         "exit".to_string(),
         "verbose".to_string(),
         "version".to_string(),
+        "config".to_string(),
+        "history".to_string(),
+        "stats".to_string(),
         "clear".to_string(),
         "test".to_string(),
         // UPnP commands
@@ -1606,6 +2612,11 @@ pub async fn handle_tui(client: Option<Arc<Mutex<RoonClient>>>, verbose_flag: bo
         "dcs-menu".to_string(),
         "dcs-set-brightness".to_string(),
         "dcs-set-display".to_string(),
+        "dcs-set-input".to_string(),
+        "dcs-set-playmode".to_string(),
+        "dcs-volume".to_string(),
+        "dcs-mute".to_string(),
+        "dcs-phase".to_string(),
     ];
 
     if client.is_some() {
@@ -1614,12 +2625,29 @@ pub async fn handle_tui(client: Option<Arc<Mutex<RoonClient>>>, verbose_flag: bo
             "status".to_string(),
             "reconnect".to_string(),
             "zones".to_string(),
+            "outputs".to_string(),
             "now-playing".to_string(),
             "queue".to_string(),
             "play".to_string(),
             "pause".to_string(),
             "stop".to_string(),
             "mute".to_string(),
+            "volume".to_string(),
+            "shuffle".to_string(),
+            "loop".to_string(),
+            "radio".to_string(),
+            "group".to_string(),
+            "ungroup".to_string(),
+            "transfer".to_string(),
+            "standby".to_string(),
+            "wake".to_string(),
+            "browse".to_string(),
+            "search".to_string(),
+            "play-item".to_string(),
+            "play-album".to_string(),
+            "play-track".to_string(),
+            "play-playlist".to_string(),
+            "play-radio".to_string(),
         ]);
     }
     commands.sort();
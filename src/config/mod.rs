@@ -0,0 +1,388 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use serde::Deserialize;
+
+/// Environment variable that overrides the state directory
+pub const STATE_DIR_ENV: &str = "ROON_RD_STATE_DIR";
+
+/// Application directory name under the platform state/data directory
+const APP_DIR_NAME: &str = "roon-rd";
+
+/// Roon state file (authorization token, paired core, extension settings)
+const ROON_STATE_FILE: &str = "roon-rd-config.json";
+
+/// Interactive command history file
+const HISTORY_FILE: &str = "history";
+
+/// Play history log (JSON Lines, one record per finished play)
+const PLAY_HISTORY_FILE: &str = "plays.jsonl";
+
+/// Album art disk cache directory
+const IMAGE_CACHE_DIR: &str = "images";
+
+/// Stable zone keys by output identity and display name
+const ZONE_KEYS_FILE: &str = "zone-keys.json";
+
+/// Legacy history file in the home directory (before the state directory existed)
+const LEGACY_HISTORY_FILE: &str = ".roon-rd_history";
+
+/// State directory set with --state-dir (takes precedence over the environment)
+static STATE_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Override the state directory (from --state-dir); must be called before any state is read
+pub fn set_state_dir(dir: PathBuf) {
+    let _ = STATE_DIR_OVERRIDE.set(dir);
+}
+
+/// Directory holding Roon state, history and other files that must survive restarts
+/// Order: --state-dir, ROON_RD_STATE_DIR, the XDG state dir (~/.local/state/roon-rd),
+/// the platform data dir (e.g. ~/Library/Application Support/roon-rd), then the current directory
+pub fn state_dir() -> PathBuf {
+    if let Some(dir) = STATE_DIR_OVERRIDE.get() {
+        return dir.clone();
+    }
+    if let Ok(dir) = std::env::var(STATE_DIR_ENV) {
+        if !dir.is_empty() {
+            return PathBuf::from(dir);
+        }
+    }
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Path of the Roon state file
+pub fn roon_state_path() -> PathBuf {
+    state_dir().join(ROON_STATE_FILE)
+}
+
+/// Path of the interactive command history file
+pub fn history_path() -> PathBuf {
+    state_dir().join(HISTORY_FILE)
+}
+
+/// Path of the play history log
+pub fn play_history_path() -> PathBuf {
+    state_dir().join(PLAY_HISTORY_FILE)
+}
+
+/// Directory of the album art disk cache
+pub fn image_cache_dir() -> PathBuf {
+    state_dir().join(IMAGE_CACHE_DIR)
+}
+
+/// Path of the stable zone key mapping
+pub fn zone_keys_path() -> PathBuf {
+    state_dir().join(ZONE_KEYS_FILE)
+}
+
+/// Create the state directory and move files from their old locations on first run
+/// (roon-rd-config.json from the current directory, .roon-rd_history from the home directory)
+pub fn init_state_dir() -> Result<PathBuf, std::io::Error> {
+    let dir = state_dir();
+    fs::create_dir_all(&dir)?;
+
+    migrate_file(Path::new(ROON_STATE_FILE), &roon_state_path());
+    if let Some(home_dir) = dirs::home_dir() {
+        migrate_file(&home_dir.join(LEGACY_HISTORY_FILE), &history_path());
+    }
+
+    Ok(dir)
+}
+
+/// Move a legacy file to its new location unless the new file already exists
+fn migrate_file(from: &Path, to: &Path) {
+    if !from.is_file() || to.exists() {
+        return;
+    }
+
+    // rename fails across filesystems, so fall back to copy and remove
+    let result = fs::rename(from, to).or_else(|_| {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    });
+
+    match result {
+        Ok(_) => log::info!("Moved {} to {}", from.display(), to.display()),
+        Err(e) => log::warn!("Failed to move {} to {}: {}", from.display(), to.display(), e),
+    }
+}
+
+/// Environment variable that overrides the config file path
+pub const CONFIG_FILE_ENV: &str = "ROON_RD_CONFIG";
+
+/// Config file name under the platform config directory
+const CONFIG_FILE: &str = "config.toml";
+
+/// Effective configuration, loaded once at startup
+static APP_CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+/// Where a configuration value came from
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    Flag,
+    RoonSettings,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File => write!(f, "config file"),
+            ConfigSource::Flag => write!(f, "command line"),
+            ConfigSource::RoonSettings => write!(f, "roon settings"),
+        }
+    }
+}
+
+/// [roon] section
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoonConfig {
+    /// Roon Core to connect to directly ("host" or "host:port")
+    pub core: Option<String>,
+    /// Don't connect to Roon (UPnP and dCS commands only)
+    pub upnp_only: bool,
+}
+
+/// [server] section
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    /// Capacity of the WebSocket broadcast channel (messages)
+    pub broadcast_capacity: usize,
+    /// Delay before broadcasting a Stopped state, so track transitions
+    /// (Playing -> Stopped -> Loading -> Playing) don't flicker the UI
+    pub stop_broadcast_delay_ms: u64,
+    /// How long /image waits for Roon to deliver an uncached image
+    pub image_timeout_ms: u64,
+    /// Cache-Control max-age for served images
+    pub image_cache_max_age_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            port: 3000,
+            broadcast_capacity: 100,
+            stop_broadcast_delay_ms: 500,
+            image_timeout_ms: 2000,
+            image_cache_max_age_secs: 3600,
+        }
+    }
+}
+
+/// [dcs] section
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DcsConfig {
+    /// dCS device for "dCS Vivaldi" zones and dcs-* commands without a host
+    pub host: String,
+    /// Delay after a zone change before re-reading the device, giving it
+    /// time to process the new stream
+    pub update_delay_ms: u64,
+    /// Interval between status polls of each dCS device in use
+    pub poll_interval_ms: u64,
+    /// Faster interval used for a while after the device state changes
+    pub fast_poll_interval_ms: u64,
+    /// Timeout for each request to a dCS device
+    pub timeout_ms: u64,
+}
+
+impl Default for DcsConfig {
+    fn default() -> Self {
+        DcsConfig {
+            host: "dcs-vivaldi.local".to_string(),
+            update_delay_ms: 200,
+            poll_interval_ms: 5000,
+            fast_poll_interval_ms: 500,
+            timeout_ms: 2000,
+        }
+    }
+}
+
+/// [upnp] section
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpnpConfig {
+    /// Delay after a zone starts playing before broadcasting zone data, giving
+    /// UPnP renderers (read while building it) time to pick up the new stream
+    pub update_delay_ms: u64,
+}
+
+impl Default for UpnpConfig {
+    fn default() -> Self {
+        UpnpConfig {
+            update_delay_ms: 200,
+        }
+    }
+}
+
+/// [display] section
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Size in pixels of album art requested from Roon
+    pub album_art_size: u32,
+    /// Seconds between artist images in the web display (0 disables rotation)
+    pub artist_image_interval_secs: u32,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            album_art_size: 300,
+            artist_image_interval_secs: 5,
+        }
+    }
+}
+
+/// [cache] section
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Memory budget of the album art cache (least recently used images are dropped)
+    pub memory_mb: u64,
+    /// Disk budget of the album art cache in the state directory (0 disables it)
+    pub disk_mb: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            memory_mb: 64,
+            disk_mb: 512,
+        }
+    }
+}
+
+/// Configuration shared by all modes: defaults, then the config file, then command-line flags
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    pub roon: RoonConfig,
+    pub server: ServerConfig,
+    pub dcs: DcsConfig,
+    pub upnp: UpnpConfig,
+    pub display: DisplayConfig,
+    pub cache: CacheConfig,
+    /// Zone or output name -> device ("dcs:host", "upnp:http://...", "none", or a bare dCS host)
+    pub zones: std::collections::BTreeMap<String, String>,
+    /// Short name -> zone display name or id, accepted wherever a zone is
+    pub aliases: std::collections::BTreeMap<String, String>,
+    /// Config file this was loaded from (None if there was no file)
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// "section.key" -> source, for keys not at their default
+    #[serde(skip)]
+    sources: std::collections::BTreeMap<String, ConfigSource>,
+}
+
+impl AppConfig {
+    /// Load the config file: `path` (--config), ROON_RD_CONFIG, or the platform config dir
+    /// A missing file gives the defaults; an invalid file is an error
+    pub fn load(path: Option<PathBuf>) -> Result<Self, String> {
+        let path = path
+            .or_else(|| std::env::var(CONFIG_FILE_ENV).ok().filter(|p| !p.is_empty()).map(PathBuf::from))
+            .unwrap_or_else(config_file_path);
+
+        if !path.is_file() {
+            log::debug!("No config file at {}, using defaults", path.display());
+            return Ok(AppConfig::default());
+        }
+
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        let mut config: AppConfig = toml::from_str(&text)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+
+        // Record every key present in the file
+        let table: toml::Table = toml::from_str(&text)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+        for (section, value) in &table {
+            if let Some(keys) = value.as_table() {
+                for key in keys.keys() {
+                    config.sources.insert(format!("{}.{}", section, key), ConfigSource::File);
+                }
+            }
+        }
+
+        log::debug!("Loaded config file {}", path.display());
+        config.path = Some(path);
+        Ok(config)
+    }
+
+    /// Apply command-line flags, which override the file
+    pub fn apply_flags(&mut self, port: Option<u16>, core: Option<String>, upnp_only: bool) {
+        if let Some(port) = port {
+            self.server.port = port;
+            self.sources.insert("server.port".to_string(), ConfigSource::Flag);
+        }
+        if let Some(core) = core {
+            self.roon.core = Some(core);
+            self.sources.insert("roon.core".to_string(), ConfigSource::Flag);
+        }
+        if upnp_only {
+            self.roon.upnp_only = true;
+            self.sources.insert("roon.upnp_only".to_string(), ConfigSource::Flag);
+        }
+    }
+
+    /// Where a "section.key" value came from
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources.get(key).cloned().unwrap_or(ConfigSource::Default)
+    }
+
+    /// All effective values as ("section.key", value)
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("roon.core".to_string(), self.roon.core.clone().unwrap_or_default()),
+            ("roon.upnp_only".to_string(), self.roon.upnp_only.to_string()),
+            ("server.port".to_string(), self.server.port.to_string()),
+            ("server.broadcast_capacity".to_string(), self.server.broadcast_capacity.to_string()),
+            ("server.stop_broadcast_delay_ms".to_string(), self.server.stop_broadcast_delay_ms.to_string()),
+            ("server.image_timeout_ms".to_string(), self.server.image_timeout_ms.to_string()),
+            ("server.image_cache_max_age_secs".to_string(), self.server.image_cache_max_age_secs.to_string()),
+            ("dcs.host".to_string(), self.dcs.host.clone()),
+            ("dcs.update_delay_ms".to_string(), self.dcs.update_delay_ms.to_string()),
+            ("dcs.poll_interval_ms".to_string(), self.dcs.poll_interval_ms.to_string()),
+            ("dcs.fast_poll_interval_ms".to_string(), self.dcs.fast_poll_interval_ms.to_string()),
+            ("dcs.timeout_ms".to_string(), self.dcs.timeout_ms.to_string()),
+            ("upnp.update_delay_ms".to_string(), self.upnp.update_delay_ms.to_string()),
+            ("display.album_art_size".to_string(), self.display.album_art_size.to_string()),
+            ("display.artist_image_interval_secs".to_string(), self.display.artist_image_interval_secs.to_string()),
+            ("cache.memory_mb".to_string(), self.cache.memory_mb.to_string()),
+            ("cache.disk_mb".to_string(), self.cache.disk_mb.to_string()),
+        ];
+        for (zone, host) in &self.zones {
+            entries.push((format!("zones.{}", zone), host.clone()));
+        }
+        for (alias, zone) in &self.aliases {
+            entries.push((format!("aliases.{}", alias), zone.clone()));
+        }
+        entries
+    }
+}
+
+/// Default config file location (~/.config/roon-rd/config.toml on Linux)
+pub fn config_file_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .unwrap_or_else(state_dir)
+        .join(CONFIG_FILE)
+}
+
+/// Make the loaded configuration available to all modules; call once at startup
+pub fn init(config: AppConfig) {
+    let _ = APP_CONFIG.set(config);
+}
+
+/// The effective configuration (defaults if init hasn't been called)
+pub fn get() -> &'static AppConfig {
+    APP_CONFIG.get_or_init(AppConfig::default)
+}
@@ -1,5 +1,36 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+use tokio::task::JoinHandle;
+use crate::roon::WsMessage;
+
+/// Polls after a state change that use the fast interval
+const FAST_POLLS_AFTER_CHANGE: u32 = 10;
+
+/// UI setting holding the menu path of the selected digital input
+const CURRENT_INPUT_PATH: &str = "dcsUiMenu:/ui/currentDigital";
+
+/// Media player play mode (its value type is "playerPlayMode")
+const PLAY_MODE_PATH: &str = "settings:/mediaPlayer/playMode";
+
+/// Volume control settings (levels are in tenths of a dB)
+/// Experimental: unlike the other paths here these haven't been checked against a device.
+/// They follow the known "dcsworker:/dcs/controls/filter" path, and the units are a guess
+const VOLUME_PATH: &str = "dcsworker:/dcs/controls/volume";
+const MUTE_PATH: &str = "dcsworker:/dcs/controls/mute";
+const BALANCE_PATH: &str = "dcsworker:/dcs/controls/balance";
+const PHASE_PATH: &str = "dcsworker:/dcs/controls/phase";
+
+/// Range of the volume control in dB (0 is full volume)
+pub const MIN_VOLUME_DB: f64 = -80.0;
+pub const MAX_VOLUME_DB: f64 = 0.0;
+
+/// Largest balance offset in dB, either way
+pub const MAX_BALANCE_DB: f64 = 6.0;
 
 /// dCS API base URL helper
 fn api_url(host: &str, endpoint: &str, path: &str, roles: &str) -> String {
@@ -33,7 +64,7 @@ pub struct DcsPlaybackInfo {
 }
 
 /// Audio format details from player data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioFormatDetails {
     pub sample_frequency: Option<i32>,
     pub bits_per_sample: Option<i32>,
@@ -41,7 +72,7 @@ pub struct AudioFormatDetails {
 }
 
 /// Device settings from the dCS device
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DcsDeviceSettings {
     pub display_brightness: Option<i32>,
     pub display_off: Option<bool>,
@@ -50,7 +81,7 @@ pub struct DcsDeviceSettings {
 
 /// Generic response type for dCS API getValue responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct DcsValueResponse {
+pub struct DcsValueResponse {
     #[serde(rename = "i32_")]
     i32_value: Option<i32>,
     #[serde(rename = "i64_")]
@@ -63,54 +94,235 @@ struct DcsValueResponse {
     value_type: Option<String>,
 }
 
-/// Get current audio format from dCS device
-pub async fn get_audio_format(host: &str) -> Result<DcsAudioFormat, Box<dyn Error>> {
-    log::info!("Fetching audio format from dCS device: {}", host);
+/// Upsampler settings from the dCS device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcsUpsamplerSettings {
+    pub output_sample_rate: Option<i32>,
+    pub filter: Option<i32>,
+}
+
+/// Digital input information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcsInputInfo {
+    pub current_input: Option<String>,
+    pub available_inputs: Vec<String>,
+}
+
+/// Play mode information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcsPlayMode {
+    pub mode: Option<String>,
+}
+
+/// Volume, mute, balance and phase of the dCS device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcsVolumeSettings {
+    pub volume_db: Option<f64>,  // 0 is full volume
+    pub mute: Option<bool>,
+    pub balance_db: Option<f64>, // Negative favours the left channel
+    pub phase_inverted: Option<bool>,
+}
+
+/// Menu item from getRows response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcsMenuItem {
+    pub title: String,
+    pub item_type: String,
+    pub path: String,
+    pub value: Option<serde_json::Value>,
+}
+
+/// Menu response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcsMenu {
+    pub title: String,
+    pub path: String,
+    pub items: Vec<DcsMenuItem>,
+}
 
-    let client = reqwest::Client::new();
+/// Errors talking to a dCS device
+#[derive(Debug, Clone, PartialEq)]
+pub enum DcsError {
+    Unreachable(String),     // Connection failed (device off, wrong host)
+    Timeout,                 // No answer within the client timeout
+    Api(String),             // The device answered with an error
+    UnexpectedShape(String), // The response didn't have the expected structure
+    InvalidArgument(String), // Rejected before sending (e.g. out of range)
+}
 
-    // Query bit depth
-    let bit_depth_url = api_url(host, "getData", "dcsworker:/dcs/currentBitDepth", "value");
-    let bit_depth_resp: Vec<DcsValueResponse> = client.get(&bit_depth_url).send().await?.json().await?;
-    let bit_depth = bit_depth_resp.first().and_then(|v| v.i32_value);
+impl fmt::Display for DcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DcsError::Unreachable(e) => write!(f, "dCS device unreachable: {}", e),
+            DcsError::Timeout => write!(f, "dCS device did not respond in time"),
+            DcsError::Api(e) => write!(f, "dCS API error: {}", e),
+            DcsError::UnexpectedShape(e) => write!(f, "Unexpected response from dCS device: {}", e),
+            DcsError::InvalidArgument(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for DcsError {}
+
+impl From<reqwest::Error> for DcsError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            DcsError::Timeout
+        } else if let Some(status) = e.status() {
+            // From error_for_status: the device answered, with an error page
+            DcsError::Api(format!("HTTP {}", status))
+        } else if e.is_decode() {
+            DcsError::UnexpectedShape(e.to_string())
+        } else {
+            DcsError::Unreachable(e.to_string())
+        }
+    }
+}
 
-    // Query sample rate
-    let sample_rate_url = api_url(host, "getData", "dcsworker:/dcs/inputSampleRateCurrent", "value");
-    let sample_rate_resp: Vec<DcsValueResponse> = client.get(&sample_rate_url).send().await?.json().await?;
-    let sample_rate = sample_rate_resp.first().and_then(|v| v.i32_value);
+impl From<serde_json::Error> for DcsError {
+    fn from(e: serde_json::Error) -> Self {
+        DcsError::UnexpectedShape(e.to_string())
+    }
+}
 
-    // Query input mode
-    let input_mode_url = api_url(host, "getData", "dcsworker:/dcs/settings/inputMode", "value");
-    let input_mode_resp: Vec<DcsValueResponse> = client.get(&input_mode_url).send().await?.json().await?;
-    let input_mode = input_mode_resp.first().and_then(|v| v.string_value.clone());
+/// A value type the dCS API can get and set (its "type" tag is also the field holding the value)
+pub trait DcsValue: Sized {
+    const TYPE: &'static str;
+    fn from_response(response: &DcsValueResponse) -> Option<Self>;
+    fn to_json(&self) -> serde_json::Value;
+}
 
-    let format = DcsAudioFormat {
-        bit_depth,
-        sample_rate,
-        input_mode,
-    };
+impl DcsValue for i32 {
+    const TYPE: &'static str = "i32_";
+    fn from_response(response: &DcsValueResponse) -> Option<Self> {
+        response.i32_value
+    }
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+}
 
-    log::debug!("dCS audio format retrieved: bit_depth={:?}, sample_rate={:?}, input_mode={:?}",
-                format.bit_depth, format.sample_rate, format.input_mode);
+impl DcsValue for i64 {
+    const TYPE: &'static str = "i64_";
+    fn from_response(response: &DcsValueResponse) -> Option<Self> {
+        response.i64_value
+    }
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+}
 
-    Ok(format)
+impl DcsValue for String {
+    const TYPE: &'static str = "string_";
+    fn from_response(response: &DcsValueResponse) -> Option<Self> {
+        response.string_value.clone()
+    }
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
 }
 
-/// Get current playback information from dCS device
-pub async fn get_playback_info(host: &str) -> Result<DcsPlaybackInfo, Box<dyn Error>> {
-    log::info!("Fetching playback info from dCS device: {}", host);
+impl DcsValue for bool {
+    const TYPE: &'static str = "bool_";
+    fn from_response(response: &DcsValueResponse) -> Option<Self> {
+        response.bool_value
+    }
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+}
+
+/// HTTP client shared by every DcsClient, so connections to each device are pooled
+static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Client for one dCS device's HTTP API
+/// Cheap to create: the underlying connection pool is shared
+#[derive(Clone, Debug)]
+pub struct DcsClient {
+    host: String,
+    http: reqwest::Client,
+    timeout: Duration, // Per request, so a hung device can't stall callers
+}
+
+impl DcsClient {
+    /// Client for a host, with the dcs.timeout_ms request timeout
+    pub fn new(host: &str) -> Self {
+        DcsClient {
+            host: host.to_string(),
+            http: HTTP.get_or_init(reqwest::Client::new).clone(),
+            timeout: Duration::from_millis(crate::config::get().dcs.timeout_ms),
+        }
+    }
+
+    /// GET a dCS API URL and parse the JSON body, turning `{"error": ...}` replies into DcsError::Api
+    async fn get_json(&self, url: &str) -> Result<serde_json::Value, DcsError> {
+        let text = self.http.get(url).timeout(self.timeout).send().await?.error_for_status()?.text().await?;
+        let json: serde_json::Value = serde_json::from_str(&text)?;
+
+        if let Some(error) = json.get("error") {
+            let message = error.get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown error");
+            return Err(DcsError::Api(message.to_string()));
+        }
+        Ok(json)
+    }
+
+    /// Read a typed value; None if the device has no value of that type at `path`
+    pub async fn get_value<T: DcsValue>(&self, path: &str) -> Result<Option<T>, DcsError> {
+        let json = self.get_json(&api_url(&self.host, "getData", path, "value")).await?;
+        let response: Vec<DcsValueResponse> = serde_json::from_value(json)?;
+        Ok(response.first().and_then(T::from_response))
+    }
+
+    /// Write a typed value; the device answers "true" on success
+    pub async fn set_value<T: DcsValue>(&self, path: &str, value: T) -> Result<(), DcsError> {
+        self.set_typed(path, T::TYPE, value.to_json()).await
+    }
+
+    /// Set a value of any dCS type, e.g. {"type":"i32_","i32_":10}
+    async fn set_typed(&self, path: &str, value_type: &str, value: serde_json::Value) -> Result<(), DcsError> {
+        let value_json = format!("{{\"type\":\"{}\",\"{}\":{}}}", value_type, value_type, value);
+        let url = format!(
+            "http://{}/api/setData?path={}&role=value&value={}",
+            self.host,
+            urlencoding::encode(path),
+            urlencoding::encode(&value_json)
+        );
+
+        let text = self.http.get(&url).timeout(self.timeout).send().await?.error_for_status()?.text().await?;
+        if text.trim() == "true" {
+            Ok(())
+        } else {
+            Err(DcsError::Api(format!("setting {} failed: {}", path, text.trim())))
+        }
+    }
+
+    /// Get current audio format
+    pub async fn get_audio_format(&self) -> Result<DcsAudioFormat, DcsError> {
+        log::debug!("Fetching audio format from dCS device: {}", self.host);
+
+        let format = DcsAudioFormat {
+            bit_depth: self.get_value("dcsworker:/dcs/currentBitDepth").await?,
+            sample_rate: self.get_value("dcsworker:/dcs/inputSampleRateCurrent").await?,
+            input_mode: self.get_value("dcsworker:/dcs/settings/inputMode").await?,
+        };
+
+        log::debug!("dCS audio format retrieved: bit_depth={:?}, sample_rate={:?}, input_mode={:?}",
+                    format.bit_depth, format.sample_rate, format.input_mode);
 
-    let client = reqwest::Client::new();
-    let url = api_url(host, "getData", "/player/data", "title,value");
+        Ok(format)
+    }
 
-    let response = client.get(&url).send().await?;
-    let text = response.text().await?;
+    /// Get current playback information
+    pub async fn get_playback_info(&self) -> Result<DcsPlaybackInfo, DcsError> {
+        log::debug!("Fetching playback info from dCS device: {}", self.host);
 
-    // Parse the complex JSON response
-    // The response is an array with two elements: first is empty string, second is the data object
-    let json: serde_json::Value = serde_json::from_str(&text)?;
+        // The response is an array with two elements: first is empty string, second is the data object
+        let json = self.get_json(&api_url(&self.host, "getData", "/player/data", "title,value")).await?;
+        let data = json.get(1)
+            .ok_or_else(|| DcsError::UnexpectedShape("missing player data".to_string()))?;
 
-    if let Some(data) = json.get(1) {
         let state = data["state"].as_str().map(|s| s.to_string());
 
         // Extract track metadata
@@ -127,16 +339,11 @@ pub async fn get_playback_info(host: &str) -> Result<DcsPlaybackInfo, Box<dyn Er
         let duration = data["status"]["duration"].as_i64().map(|d| d as i32);
 
         // Extract audio format from resources
-        let resources = &media_data["resources"];
-        let audio_format = if let Some(resource) = resources.get(0) {
-            Some(AudioFormatDetails {
-                sample_frequency: resource["sampleFrequency"].as_i64().map(|v| v as i32),
-                bits_per_sample: resource["bitsPerSample"].as_i64().map(|v| v as i32),
-                nr_audio_channels: resource["nrAudioChannels"].as_i64().map(|v| v as i32),
-            })
-        } else {
-            None
-        };
+        let audio_format = media_data["resources"].get(0).map(|resource| AudioFormatDetails {
+            sample_frequency: resource["sampleFrequency"].as_i64().map(|v| v as i32),
+            bits_per_sample: resource["bitsPerSample"].as_i64().map(|v| v as i32),
+            nr_audio_channels: resource["nrAudioChannels"].as_i64().map(|v| v as i32),
+        });
 
         let playback_info = DcsPlaybackInfo {
             state,
@@ -163,329 +370,466 @@ pub async fn get_playback_info(host: &str) -> Result<DcsPlaybackInfo, Box<dyn Er
         );
 
         Ok(playback_info)
-    } else {
-        Err("Invalid response format from dCS API".into())
     }
-}
 
-/// Get device settings from dCS device
-pub async fn get_device_settings(host: &str) -> Result<DcsDeviceSettings, Box<dyn Error>> {
-    log::info!("Fetching device settings from dCS device: {}", host);
+    /// Get device settings
+    pub async fn get_device_settings(&self) -> Result<DcsDeviceSettings, DcsError> {
+        log::debug!("Fetching device settings from dCS device: {}", self.host);
 
-    let client = reqwest::Client::new();
+        let settings = DcsDeviceSettings {
+            display_brightness: self.get_value("dcsworker:/dcs/unitSettings/displayBrightness").await?,
+            display_off: self.get_value("dcsworker:/dcs/unitSettings/displayOff").await?,
+            sync_mode: self.get_value("dcsworker:/dcs/unitSettings/syncMode").await?,
+        };
 
-    // Query display brightness
-    let brightness_url = api_url(host, "getData", "dcsworker:/dcs/unitSettings/displayBrightness", "value");
-    let brightness_resp: Vec<DcsValueResponse> = client.get(&brightness_url).send().await?.json().await?;
-    let display_brightness = brightness_resp.first().and_then(|v| v.i32_value);
+        log::debug!("dCS device settings retrieved: display_brightness={:?}, display_off={:?}, sync_mode={:?}",
+                    settings.display_brightness, settings.display_off, settings.sync_mode);
 
-    // Query display off state
-    let display_off_url = api_url(host, "getData", "dcsworker:/dcs/unitSettings/displayOff", "value");
-    let display_off_resp: Vec<DcsValueResponse> = client.get(&display_off_url).send().await?.json().await?;
-    let display_off = display_off_resp.first().and_then(|v| v.bool_value);
+        Ok(settings)
+    }
 
-    // Query sync mode
-    let sync_mode_url = api_url(host, "getData", "dcsworker:/dcs/unitSettings/syncMode", "value");
-    let sync_mode_resp: Vec<DcsValueResponse> = client.get(&sync_mode_url).send().await?.json().await?;
-    let sync_mode = sync_mode_resp.first().and_then(|v| v.string_value.clone());
+    /// Get upsampler settings
+    pub async fn get_upsampler_settings(&self) -> Result<DcsUpsamplerSettings, DcsError> {
+        log::debug!("Fetching upsampler settings from dCS device: {}", self.host);
 
-    let settings = DcsDeviceSettings {
-        display_brightness,
-        display_off,
-        sync_mode,
-    };
-
-    log::debug!("dCS device settings retrieved: display_brightness={:?}, display_off={:?}, sync_mode={:?}",
-                settings.display_brightness, settings.display_off, settings.sync_mode);
+        let settings = DcsUpsamplerSettings {
+            output_sample_rate: self.get_value("dcsworker:/dcs/settings/outputSampleRate").await?,
+            filter: self.get_value("dcsworker:/dcs/controls/filter").await?,
+        };
 
-    Ok(settings)
-}
+        log::debug!("dCS upsampler settings retrieved: output_sample_rate={:?}, filter={:?}",
+                    settings.output_sample_rate, settings.filter);
 
-/// Upsampler settings from the dCS device
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DcsUpsamplerSettings {
-    pub output_sample_rate: Option<i32>,
-    pub filter: Option<i32>,
-}
+        Ok(settings)
+    }
 
-/// Digital input information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DcsInputInfo {
-    pub current_input: Option<String>,
-    pub available_inputs: Vec<String>,
-}
+    /// Get current playback position in milliseconds
+    pub async fn get_playback_position(&self) -> Result<i64, DcsError> {
+        log::debug!("Fetching playback position from dCS device: {}", self.host);
 
-/// Play mode information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DcsPlayMode {
-    pub mode: Option<String>,
-}
+        let position = self.get_value::<i64>("/player/data/playTime").await?
+            .ok_or_else(|| DcsError::UnexpectedShape("no playback position available".to_string()))?;
 
-/// Menu item from getRows response
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DcsMenuItem {
-    pub title: String,
-    pub item_type: String,
-    pub path: String,
-    pub value: Option<serde_json::Value>,
-}
+        log::debug!("dCS playback position retrieved: {} ms", position);
+        Ok(position)
+    }
 
-/// Menu response
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DcsMenu {
-    pub title: String,
-    pub path: String,
-    pub items: Vec<DcsMenuItem>,
-}
+    /// Get digital input information
+    pub async fn get_input_info(&self) -> Result<DcsInputInfo, DcsError> {
+        log::debug!("Fetching input info from dCS device: {}", self.host);
+
+        let (current_path, inputs) = self.get_inputs().await?;
+
+        // Inputs outside the menu (or no menu) need the title from the input's own entry
+        let mut current_input = None;
+        if let Some(path) = current_path {
+            current_input = match inputs.iter().find(|input| input.path == path) {
+                Some(input) => Some(input.title.clone()),
+                None => self.get_json(&api_url(&self.host, "getData", &path, "title,value")).await?
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+            };
+        }
 
-/// Get upsampler settings from dCS device
-pub async fn get_upsampler_settings(host: &str) -> Result<DcsUpsamplerSettings, Box<dyn Error>> {
-    log::info!("Fetching upsampler settings from dCS device: {}", host);
+        // Only inputs set_input can select; none if the device doesn't list its input menu
+        let available_inputs = inputs.into_iter().map(|input| input.title).collect();
 
-    let client = reqwest::Client::new();
+        let input_info = DcsInputInfo {
+            current_input,
+            available_inputs,
+        };
 
-    // Query output sample rate
-    let output_rate_url = api_url(host, "getData", "dcsworker:/dcs/settings/outputSampleRate", "value");
-    let output_rate_resp: Vec<DcsValueResponse> = client.get(&output_rate_url).send().await?.json().await?;
-    let output_sample_rate = output_rate_resp.first().and_then(|v| v.i32_value);
+        log::debug!("dCS input info retrieved: current_input={:?}, available_inputs={:?}",
+                    input_info.current_input, input_info.available_inputs);
 
-    // Query filter setting
-    let filter_url = api_url(host, "getData", "dcsworker:/dcs/controls/filter", "value");
-    let filter_resp: Vec<DcsValueResponse> = client.get(&filter_url).send().await?.json().await?;
-    let filter = filter_resp.first().and_then(|v| v.i32_value);
+        Ok(input_info)
+    }
 
-    let settings = DcsUpsamplerSettings {
-        output_sample_rate,
-        filter,
-    };
+    /// Menu path of the current input and the entries of the menu it is listed in
+    /// (empty if the device doesn't report a current input)
+    async fn get_inputs(&self) -> Result<(Option<String>, Vec<DcsMenuItem>), DcsError> {
+        // The current input is a path to the input's menu entry, which has its title
+        let current_path: Option<String> = self.get_json(&api_url(&self.host, "getData", CURRENT_INPUT_PATH, "title,value")).await?
+            .get(1)
+            .and_then(|v| v.get("string_"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
 
-    log::debug!("dCS upsampler settings retrieved: output_sample_rate={:?}, filter={:?}",
-                settings.output_sample_rate, settings.filter);
+        let inputs = match current_path.as_deref().and_then(|path| path.rsplit_once('/')) {
+            Some((menu_path, _)) => self.get_menu(menu_path).await?.items,
+            None => Vec::new(),
+        };
 
-    Ok(settings)
-}
+        Ok((current_path, inputs))
+    }
 
-/// Get current playback position in milliseconds
-pub async fn get_playback_position(host: &str) -> Result<i64, Box<dyn Error>> {
-    log::info!("Fetching playback position from dCS device: {}", host);
+    /// Select a digital input by name (case-insensitive), checked against the inputs the device lists
+    pub async fn set_input(&self, input: &str) -> Result<(), DcsError> {
+        log::info!("Setting input on dCS device: {} -> {}", self.host, input);
 
-    let client = reqwest::Client::new();
-    let url = api_url(host, "getData", "/player/data/playTime", "value,path");
+        let (_, inputs) = self.get_inputs().await?;
+        if inputs.is_empty() {
+            return Err(DcsError::UnexpectedShape("device did not list its inputs".to_string()));
+        }
 
-    let response: Vec<DcsValueResponse> = client.get(&url).send().await?.json().await?;
+        let Some(entry) = inputs.iter().find(|entry| entry.title.eq_ignore_ascii_case(input.trim())) else {
+            let available: Vec<&str> = inputs.iter().map(|entry| entry.title.as_str()).collect();
+            return Err(DcsError::InvalidArgument(format!("Unknown input '{}' (available: {})", input, available.join(", "))));
+        };
 
-    if let Some(first) = response.first() {
-        if let Some(i64_val) = first.i64_value {
-            log::debug!("dCS playback position retrieved: {} ms", i64_val);
-            return Ok(i64_val);
-        }
+        self.set_value(CURRENT_INPUT_PATH, entry.path.clone()).await
     }
 
-    log::debug!("dCS playback position: No value available");
-    Err("No playback position available".into())
-}
+    /// Get play mode
+    pub async fn get_play_mode(&self) -> Result<DcsPlayMode, DcsError> {
+        log::debug!("Fetching play mode from dCS device: {}", self.host);
+
+        let json = self.get_json(&api_url(&self.host, "getData", PLAY_MODE_PATH, "value")).await?;
+        let mode = json.get(0)
+            .and_then(|v| v.get("playerPlayMode"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
 
-/// Get digital input information
-pub async fn get_input_info(host: &str) -> Result<DcsInputInfo, Box<dyn Error>> {
-    log::info!("Fetching input info from dCS device: {}", host);
+        let play_mode = DcsPlayMode { mode };
 
-    let client = reqwest::Client::new();
+        log::debug!("dCS play mode retrieved: mode={:?}", play_mode.mode);
 
-    // Get current input
-    let current_url = api_url(host, "getData", "dcsUiMenu:/ui/currentDigital", "title,value");
-    let response = client.get(&current_url).send().await?;
-    let text = response.text().await?;
-    let json: serde_json::Value = serde_json::from_str(&text)?;
+        Ok(play_mode)
+    }
 
-    // Extract current input path and get its title
-    let current_path = json.get(1)
-        .and_then(|v| v.get("string_"))
-        .and_then(|v| v.as_str());
+    /// Set play mode (a mode name as reported by get_play_mode)
+    pub async fn set_play_mode(&self, mode: &str) -> Result<(), DcsError> {
+        log::info!("Setting play mode on dCS device: {} -> {}", self.host, mode);
 
-    let mut current_input = None;
-    if let Some(path) = current_path {
-        // Get the title for this input
-        let input_url = api_url(host, "getData", path, "title,value");
-        let input_response = client.get(&input_url).send().await?;
-        let input_text = input_response.text().await?;
-        let input_json: serde_json::Value = serde_json::from_str(&input_text)?;
-        current_input = input_json.get(0).and_then(|v| v.as_str()).map(|s| s.to_string());
+        if mode.trim().is_empty() {
+            return Err(DcsError::InvalidArgument("Play mode must not be empty".to_string()));
+        }
+        self.set_typed(PLAY_MODE_PATH, "playerPlayMode", serde_json::json!(mode.trim())).await
     }
 
-    // List of known digital inputs
-    let available_inputs = vec![
-        "Network".to_string(),
-        "AES1".to_string(),
-        "SPDIF1".to_string(),
-        "SPDIF2".to_string(),
-    ];
+    /// Get menu items using getRows
+    pub async fn get_menu(&self, path: &str) -> Result<DcsMenu, DcsError> {
+        log::debug!("Fetching menu from dCS device: {} -> {}", self.host, path);
 
-    let input_info = DcsInputInfo {
-        current_input,
-        available_inputs,
-    };
+        let url = format!(
+            "http://{}/api/getRows?path={}&roles=title,icon,type,path,value&from=0&to=100",
+            self.host,
+            urlencoding::encode(path)
+        );
+        let json = self.get_json(&url).await?;
 
-    log::debug!("dCS input info retrieved: current_input={:?}, available_inputs={:?}",
-                input_info.current_input, input_info.available_inputs);
+        // Extract menu title and path
+        let roles = json.get("roles")
+            .ok_or_else(|| DcsError::UnexpectedShape("missing roles in response".to_string()))?;
+        let title = roles.get("title")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        let menu_path = roles.get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or(path)
+            .to_string();
+
+        // Extract rows
+        let rows = json.get("rows")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| DcsError::UnexpectedShape("missing rows in response".to_string()))?;
+
+        let mut items = Vec::new();
+        for row in rows {
+            if let Some(row_array) = row.as_array() {
+                if row_array.len() >= 4 {
+                    let item_title = row_array[0].as_str().unwrap_or("").to_string();
+                    let item_type = row_array[2].as_str().unwrap_or("unknown").to_string();
+                    let item_path = row_array[3].as_str().unwrap_or("").to_string();
+                    let value = row_array.get(4).filter(|v| !v.is_null()).cloned();
+
+                    items.push(DcsMenuItem {
+                        title: item_title,
+                        item_type,
+                        path: item_path,
+                        value,
+                    });
+                }
+            }
+        }
 
-    Ok(input_info)
-}
+        let menu = DcsMenu {
+            title,
+            path: menu_path,
+            items,
+        };
 
-/// Get play mode
-pub async fn get_play_mode(host: &str) -> Result<DcsPlayMode, Box<dyn Error>> {
-    log::info!("Fetching play mode from dCS device: {}", host);
+        log::debug!("dCS menu retrieved: title={:?}, path={:?}, item_count={}",
+                    menu.title, menu.path, menu.items.len());
 
-    let client = reqwest::Client::new();
-    let url = api_url(host, "getData", "settings:/mediaPlayer/playMode", "value");
+        Ok(menu)
+    }
 
-    let response = client.get(&url).send().await?;
-    let text = response.text().await?;
-    let json: serde_json::Value = serde_json::from_str(&text)?;
+    /// Get volume, mute, balance and phase (experimental, see VOLUME_PATH)
+    pub async fn get_volume_settings(&self) -> Result<DcsVolumeSettings, DcsError> {
+        log::debug!("Fetching volume settings from dCS device: {}", self.host);
 
-    let mode = json.get(0)
-        .and_then(|v| v.get("playerPlayMode"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
+        let settings = DcsVolumeSettings {
+            volume_db: self.get_value::<i32>(VOLUME_PATH).await?.map(from_tenths),
+            mute: self.get_value(MUTE_PATH).await?,
+            balance_db: self.get_value::<i32>(BALANCE_PATH).await?.map(from_tenths),
+            phase_inverted: self.get_value(PHASE_PATH).await?,
+        };
 
-    let play_mode = DcsPlayMode { mode };
+        log::debug!("dCS volume settings retrieved: volume_db={:?}, mute={:?}, balance_db={:?}, phase_inverted={:?}",
+                    settings.volume_db, settings.mute, settings.balance_db, settings.phase_inverted);
 
-    log::debug!("dCS play mode retrieved: mode={:?}", play_mode.mode);
+        Ok(settings)
+    }
 
-    Ok(play_mode)
-}
+    /// Set volume in dB (MIN_VOLUME_DB to MAX_VOLUME_DB), experimental like the other volume controls
+    pub async fn set_volume(&self, volume_db: f64) -> Result<(), DcsError> {
+        log::info!("Setting volume on dCS device: {} -> {} dB", self.host, volume_db);
 
-/// Get menu items using getRows
-pub async fn get_menu(host: &str, path: &str) -> Result<DcsMenu, Box<dyn Error>> {
-    log::info!("Fetching menu from dCS device: {} -> {}", host, path);
+        check_volume(volume_db)?;
+        self.set_value(VOLUME_PATH, to_tenths(volume_db)).await
+    }
 
-    let client = reqwest::Client::new();
+    /// Mute or unmute
+    pub async fn set_mute(&self, mute: bool) -> Result<(), DcsError> {
+        log::info!("Setting mute on dCS device: {} -> {}", self.host, mute);
+        self.set_value(MUTE_PATH, mute).await
+    }
 
-    // Build getRows URL
-    let url = format!(
-        "http://{}/api/getRows?path={}&roles=title,icon,type,path,value&from=0&to=100",
-        host,
-        urlencoding::encode(path)
-    );
+    /// Set balance in dB (negative favours the left channel)
+    pub async fn set_balance(&self, balance_db: f64) -> Result<(), DcsError> {
+        log::info!("Setting balance on dCS device: {} -> {} dB", self.host, balance_db);
 
-    let response = client.get(&url).send().await?;
-    let text = response.text().await?;
-    let json: serde_json::Value = serde_json::from_str(&text)?;
+        check_balance(balance_db)?;
+        self.set_value(BALANCE_PATH, to_tenths(balance_db)).await
+    }
 
-    // Check for error
-    if let Some(error) = json.get("error") {
-        let error_msg = error.get("message")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown error");
-        return Err(format!("API error: {}", error_msg).into());
-    }
-
-    // Extract menu title and path
-    let roles = json.get("roles").ok_or("Missing roles in response")?;
-    let title = roles.get("title")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Unknown")
-        .to_string();
-    let menu_path = roles.get("path")
-        .and_then(|v| v.as_str())
-        .unwrap_or(path)
-        .to_string();
-
-    // Extract rows
-    let rows = json.get("rows")
-        .and_then(|v| v.as_array())
-        .ok_or("Missing rows in response")?;
-
-    let mut items = Vec::new();
-    for row in rows {
-        if let Some(row_array) = row.as_array() {
-            if row_array.len() >= 4 {
-                let item_title = row_array[0].as_str().unwrap_or("").to_string();
-                let item_type = row_array[2].as_str().unwrap_or("unknown").to_string();
-                let item_path = row_array[3].as_str().unwrap_or("").to_string();
-                let value = if !row_array[4].is_null() {
-                    Some(row_array[4].clone())
-                } else {
-                    None
-                };
-
-                items.push(DcsMenuItem {
-                    title: item_title,
-                    item_type,
-                    path: item_path,
-                    value,
-                });
-            }
-        }
+    /// Set absolute phase (true inverts it)
+    pub async fn set_phase(&self, inverted: bool) -> Result<(), DcsError> {
+        log::info!("Setting phase on dCS device: {} -> {}", self.host, if inverted { "inverted" } else { "normal" });
+        self.set_value(PHASE_PATH, inverted).await
     }
 
-    let menu = DcsMenu {
-        title,
-        path: menu_path,
-        items,
-    };
+    /// Set display brightness (0-15 range)
+    pub async fn set_display_brightness(&self, brightness: i32) -> Result<(), DcsError> {
+        log::info!("Setting display brightness on dCS device: {} -> {}", self.host, brightness);
 
-    log::debug!("dCS menu retrieved: title={:?}, path={:?}, item_count={}",
-                menu.title, menu.path, menu.items.len());
+        if !(0..=15).contains(&brightness) {
+            return Err(DcsError::InvalidArgument("Brightness must be between 0 and 15".to_string()));
+        }
+
+        self.set_value("dcsworker:/dcs/unitSettings/displayBrightness", brightness).await?;
+        log::debug!("dCS display brightness set successfully: {}", brightness);
+        Ok(())
+    }
+
+    /// Set display on/off state
+    pub async fn set_display_off(&self, off: bool) -> Result<(), DcsError> {
+        log::info!("Setting display off on dCS device: {} -> {}", self.host, off);
 
-    Ok(menu)
+        self.set_value("dcsworker:/dcs/unitSettings/displayOff", off).await?;
+        log::debug!("dCS display off set successfully: {}", off);
+        Ok(())
+    }
 }
 
-/// Set display brightness (0-15 range)
-pub async fn set_display_brightness(host: &str, brightness: i32) -> Result<(), Box<dyn Error>> {
-    log::info!("Setting display brightness on dCS device: {} -> {}", host, brightness);
+/// Check a volume is within MIN_VOLUME_DB..=MAX_VOLUME_DB
+pub fn check_volume(volume_db: f64) -> Result<(), DcsError> {
+    if (MIN_VOLUME_DB..=MAX_VOLUME_DB).contains(&volume_db) {
+        Ok(())
+    } else {
+        Err(DcsError::InvalidArgument(format!("Volume must be between {} and {} dB", MIN_VOLUME_DB, MAX_VOLUME_DB)))
+    }
+}
 
-    if brightness < 0 || brightness > 15 {
-        return Err("Brightness must be between 0 and 15".into());
+/// Check a balance is within -MAX_BALANCE_DB..=MAX_BALANCE_DB
+pub fn check_balance(balance_db: f64) -> Result<(), DcsError> {
+    if (-MAX_BALANCE_DB..=MAX_BALANCE_DB).contains(&balance_db) {
+        Ok(())
+    } else {
+        Err(DcsError::InvalidArgument(format!("Balance must be between -{} and {} dB", MAX_BALANCE_DB, MAX_BALANCE_DB)))
     }
+}
 
-    let client = reqwest::Client::new();
+/// dB to the device's tenths of a dB
+fn to_tenths(db: f64) -> i32 {
+    (db * 10.0).round() as i32
+}
 
-    // Build setData URL with JSON value
-    let value_json = format!("{{\"type\":\"i32_\",\"i32_\":{}}}", brightness);
-    let url = format!(
-        "http://{}/api/setData?path={}&role=value&value={}",
-        host,
-        urlencoding::encode("dcsworker:/dcs/unitSettings/displayBrightness"),
-        urlencoding::encode(&value_json)
-    );
+/// The device's tenths of a dB to dB
+fn from_tenths(tenths: i32) -> f64 {
+    tenths as f64 / 10.0
+}
 
-    let response = client.get(&url).send().await?;
-    let text = response.text().await?;
+/// Latest known state of a dCS device, kept up to date by the poller
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DcsDeviceState {
+    pub host: String,
+    pub state: Option<String>,  // Playback state
+    pub format: Option<String>, // e.g. "96 kHz 24 bit", None when the stream has no usable format
+    pub audio_format: Option<AudioFormatDetails>,
+    pub input: Option<DcsInputInfo>,
+    pub play_mode: Option<DcsPlayMode>,
+    pub upsampler: Option<DcsUpsamplerSettings>,
+    pub settings: Option<DcsDeviceSettings>,
+    pub volume: Option<DcsVolumeSettings>,
+    pub error: Option<String>, // First error of the last poll (e.g. the device is unreachable)
+}
 
-    // API returns "true" on success
-    if text.trim() == "true" {
-        log::debug!("dCS display brightness set successfully: {}", brightness);
-        Ok(())
+/// Format label for an audio format ("96 kHz 24 bit"), None without a sample rate or bit depth
+pub fn format_label(audio_format: &AudioFormatDetails) -> Option<String> {
+    let bits = audio_format.bits_per_sample.filter(|bits| *bits > 0)?;
+    let freq = audio_format.sample_frequency?;
+    let sample_rate_str = if freq >= 1000 {
+        format!("{} kHz", freq / 1000)
     } else {
-        log::debug!("dCS display brightness set failed: {}", text);
-        Err(format!("Failed to set brightness: {}", text).into())
+        format!("{} Hz", freq)
+    };
+    Some(format!("{} {} bit", sample_rate_str, bits))
+}
+
+/// Read the full state of a dCS device; parts that fail are left empty
+pub async fn get_device_state(host: &str) -> DcsDeviceState {
+    let client = DcsClient::new(host);
+    let (playback, input, play_mode, upsampler, settings, volume) = tokio::join!(
+        client.get_playback_info(),
+        client.get_input_info(),
+        client.get_play_mode(),
+        client.get_upsampler_settings(),
+        client.get_device_settings(),
+        client.get_volume_settings(),
+    );
+
+    let error = [
+        playback.as_ref().err(),
+        input.as_ref().err(),
+        play_mode.as_ref().err(),
+        upsampler.as_ref().err(),
+        settings.as_ref().err(),
+        // Volume settings are experimental (see VOLUME_PATH), so they don't make the device unreadable
+    ].into_iter().flatten().next().map(|e| e.to_string());
+    let audio_format = playback.as_ref().ok().and_then(|info| info.audio_format.clone());
+
+    DcsDeviceState {
+        host: host.to_string(),
+        state: playback.as_ref().ok().and_then(|info| info.state.clone()),
+        format: audio_format.as_ref().and_then(format_label),
+        audio_format,
+        input: input.ok(),
+        play_mode: play_mode.ok(),
+        upsampler: upsampler.ok(),
+        settings: settings.ok(),
+        volume: volume.ok(),
+        error,
     }
 }
 
-/// Set display on/off state
-pub async fn set_display_off(host: &str, off: bool) -> Result<(), Box<dyn Error>> {
-    log::info!("Setting display off on dCS device: {} -> {}", host, off);
+/// A polling task and the handle used to wake it early
+struct PollTask {
+    wakeup: Arc<Notify>,
+    task: JoinHandle<()>,
+}
 
-    let client = reqwest::Client::new();
+/// Background status polling for dCS devices, one task per host
+/// Hosts are polled from the first `watch`, and every change is broadcast as `WsMessage::DeviceChanged`
+#[derive(Clone)]
+pub struct DcsPoller {
+    states: Arc<RwLock<HashMap<String, DcsDeviceState>>>, // host -> latest state
+    tasks: Arc<Mutex<HashMap<String, PollTask>>>,          // host -> polling task
+    ws_tx: broadcast::Sender<WsMessage>,
+}
 
-    // Build setData URL with JSON value
-    let value_json = format!("{{\"type\":\"bool_\",\"bool_\":{}}}", off);
-    let url = format!(
-        "http://{}/api/setData?path={}&role=value&value={}",
-        host,
-        urlencoding::encode("dcsworker:/dcs/unitSettings/displayOff"),
-        urlencoding::encode(&value_json)
-    );
+impl DcsPoller {
+    pub fn new(ws_tx: broadcast::Sender<WsMessage>) -> Self {
+        DcsPoller {
+            states: Arc::new(RwLock::new(HashMap::new())),
+            tasks: Arc::new(Mutex::new(HashMap::new())),
+            ws_tx,
+        }
+    }
 
-    let response = client.get(&url).send().await?;
-    let text = response.text().await?;
+    /// Start polling a host (nothing happens if it is already polled)
+    pub async fn watch(&self, host: &str) {
+        let mut tasks = self.tasks.lock().await;
+        if tasks.contains_key(host) {
+            return;
+        }
 
-    // API returns "true" on success
-    if text.trim() == "true" {
-        log::debug!("dCS display off set successfully: {}", off);
-        Ok(())
-    } else {
-        log::debug!("dCS display off set failed: {}", text);
-        Err(format!("Failed to set display off: {}", text).into())
+        log::debug!("Starting dCS status poller for {}", host);
+        let wakeup = Arc::new(Notify::new());
+        let task = tokio::spawn(poll_loop(host.to_string(), self.states.clone(), wakeup.clone(), self.ws_tx.clone()));
+        tasks.insert(host.to_string(), PollTask { wakeup, task });
+    }
+
+    /// Re-read a host soon (after the dcs.update_delay_ms settle time), e.g. when its zone starts a new stream
+    /// Only wakes a host that is already polled; pollers are started by watch
+    pub async fn poke(&self, host: &str) {
+        if let Some(poll) = self.tasks.lock().await.get(host) {
+            poll.wakeup.notify_one();
+        }
+    }
+
+    /// Stop polling hosts that are no longer in use
+    pub async fn retain(&self, hosts: &[String]) {
+        let mut tasks = self.tasks.lock().await;
+        let mut states = self.states.write().await;
+        tasks.retain(|host, poll| {
+            let keep = hosts.contains(host);
+            if !keep {
+                log::debug!("Stopping dCS status poller for {}", host);
+                poll.task.abort();
+                states.remove(host);
+            }
+            keep
+        });
+    }
+
+    /// Latest state of a host, None until its first poll completes
+    pub async fn get(&self, host: &str) -> Option<DcsDeviceState> {
+        self.states.read().await.get(host).cloned()
+    }
+}
+
+/// Poll one host until aborted: every dcs.poll_interval_ms, or dcs.fast_poll_interval_ms for a while after a change
+async fn poll_loop(
+    host: String,
+    states: Arc<RwLock<HashMap<String, DcsDeviceState>>>,
+    wakeup: Arc<Notify>,
+    ws_tx: broadcast::Sender<WsMessage>,
+) {
+    use tokio::time::{sleep, Duration};
+
+    let mut fast_polls = 0;
+    loop {
+        let state = get_device_state(&host).await;
+        let previous = states.read().await.get(&host).cloned();
+        if previous.as_ref() != Some(&state) {
+            if let Some(error) = state.error.as_ref().filter(|_| previous.as_ref().map_or(true, |p| p.error != state.error)) {
+                log::warn!("dCS device {} not fully readable: {}", host, error);
+            }
+            log::debug!("dCS device {} changed: state={:?}, format={:?}", host, state.state, state.format);
+            states.write().await.insert(host.clone(), state.clone());
+            let _ = ws_tx.send(WsMessage::DeviceChanged { state });
+            fast_polls = FAST_POLLS_AFTER_CHANGE;
+        }
+
+        let config = &crate::config::get().dcs;
+        let interval_ms = if fast_polls > 0 {
+            fast_polls -= 1;
+            config.fast_poll_interval_ms
+        } else {
+            config.poll_interval_ms
+        };
+
+        tokio::select! {
+            _ = sleep(Duration::from_millis(interval_ms)) => {}
+            _ = wakeup.notified() => {
+                // Give the device time to pick up the new stream
+                sleep(Duration::from_millis(config.update_delay_ms)).await;
+                fast_polls = FAST_POLLS_AFTER_CHANGE;
+            }
+        }
     }
 }
@@ -0,0 +1,224 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use roon_api::transport::{State, Zone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Plays shorter than this (skipped tracks) are not logged
+const MIN_PLAYED_SECS: u64 = 1;
+
+/// One finished play, stored as a line of JSON in the play history log
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayRecord {
+    pub zone_id: String,
+    pub zone_name: String,
+    pub track: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub image_key: Option<String>,
+    pub length: Option<u32>, // Track length in seconds
+    pub started_at: DateTime<Utc>,
+    pub played_secs: u64, // Time actually spent playing (pauses excluded)
+}
+
+/// The track currently playing in a zone, until it changes
+struct CurrentPlay {
+    record: PlayRecord,
+    playing_since: Option<Instant>, // Set while the zone is playing
+    played: Duration,
+}
+
+impl CurrentPlay {
+    fn played(&self) -> Duration {
+        self.played + self.playing_since.map(|since| since.elapsed()).unwrap_or_default()
+    }
+}
+
+/// Watches zone updates and appends a record to the play history log when a track finishes
+#[derive(Clone)]
+pub struct PlayHistory {
+    current: Arc<Mutex<HashMap<String, CurrentPlay>>>, // zone_id -> track playing now
+}
+
+impl PlayHistory {
+    pub fn new() -> Self {
+        PlayHistory {
+            current: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Update from a changed zone; logs the previous track if the now playing track changed
+    pub async fn on_zone(&self, zone: &Zone) {
+        let finished = self.update(zone).await;
+        append(finished.into_iter().collect()).await;
+    }
+
+    /// Track the zone's now playing track, returning the play it replaced (if any)
+    async fn update(&self, zone: &Zone) -> Option<CurrentPlay> {
+        let mut current = self.current.lock().await;
+        let playing = zone.state == State::Playing;
+
+        let now_playing = zone.now_playing.as_ref().filter(|np| !np.three_line.line1.is_empty());
+        let Some(now_playing) = now_playing else {
+            return current.remove(&zone.zone_id);
+        };
+
+        let three_line = &now_playing.three_line;
+        let same_track = current.get(&zone.zone_id).map_or(false, |play| {
+            play.record.track == three_line.line1
+                && play.record.artist.as_deref().unwrap_or("") == three_line.line2
+                && play.record.album.as_deref().unwrap_or("") == three_line.line3
+        });
+
+        if same_track {
+            // Only the play state may have changed
+            let play = current.get_mut(&zone.zone_id).unwrap();
+            match (play.playing_since, playing) {
+                (None, true) => play.playing_since = Some(Instant::now()),
+                (Some(since), false) => {
+                    play.played += since.elapsed();
+                    play.playing_since = None;
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        let finished = current.remove(&zone.zone_id);
+
+        let non_empty = |s: &str| if s.is_empty() { None } else { Some(s.to_string()) };
+        current.insert(zone.zone_id.clone(), CurrentPlay {
+            record: PlayRecord {
+                zone_id: zone.zone_id.clone(),
+                zone_name: zone.display_name.clone(),
+                track: three_line.line1.clone(),
+                artist: non_empty(&three_line.line2),
+                album: non_empty(&three_line.line3),
+                image_key: now_playing.image_key.clone(),
+                length: now_playing.length,
+                started_at: Utc::now(),
+                played_secs: 0,
+            },
+            playing_since: if playing { Some(Instant::now()) } else { None },
+            played: Duration::ZERO,
+        });
+        finished
+    }
+
+    /// Log the track playing in a zone that went away
+    pub async fn on_zone_removed(&self, zone_id: &str) {
+        let finished = self.current.lock().await.remove(zone_id);
+        append(finished.into_iter().collect()).await;
+    }
+
+    /// Log every track in progress (e.g. when the Core connection is lost)
+    pub async fn finish_all(&self) {
+        let finished: Vec<CurrentPlay> = self.current.lock().await.drain().map(|(_, play)| play).collect();
+        append(finished).await;
+    }
+}
+
+/// Append finished plays to the log, skipping plays that barely started
+/// The file is written on the blocking pool; callers must not hold the current plays lock
+async fn append(plays: Vec<CurrentPlay>) {
+    let records: Vec<PlayRecord> = plays.into_iter()
+        .map(|play| PlayRecord { played_secs: play.played().as_secs(), ..play.record })
+        .filter(|record| record.played_secs >= MIN_PLAYED_SECS)
+        .collect();
+    if records.is_empty() {
+        return;
+    }
+
+    let path = crate::config::play_history_path();
+    let write_path = path.clone();
+    let result = tokio::task::spawn_blocking(move || -> Result<Vec<PlayRecord>, String> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&write_path).map_err(|e| e.to_string())?;
+        for record in &records {
+            let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        }
+        Ok(records)
+    }).await.map_err(|e| e.to_string()).and_then(|result| result);
+
+    match result {
+        Ok(records) => {
+            for record in records {
+                log::debug!("Logged play: {} in {} ({}s)", record.track, record.zone_name, record.played_secs);
+            }
+        }
+        Err(e) => log::error!("Failed to write play history to {}: {}", path.display(), e),
+    }
+}
+
+/// Parse a `since` filter: an RFC 3339 timestamp or a local date (YYYY-MM-DD)
+pub fn parse_since(since: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(since) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(since, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid time '{}' (use YYYY-MM-DD or an RFC 3339 timestamp)", since))?;
+    Local.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("Invalid local time '{}'", since))
+}
+
+/// Parse an `until` filter like `since`; a plain date includes the whole day
+pub fn parse_until(until: &str) -> Result<DateTime<Utc>, String> {
+    let time = parse_since(until)?;
+    if DateTime::parse_from_rfc3339(until).is_ok() {
+        Ok(time)
+    } else {
+        Ok(time + chrono::Duration::days(1))
+    }
+}
+
+/// Read the play history log on the blocking pool (see read_history)
+pub async fn read_history_async(zone: Option<String>, since: Option<DateTime<Utc>>) -> Result<Vec<PlayRecord>, String> {
+    tokio::task::spawn_blocking(move || read_history(zone.as_deref(), since))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Read the play history log, oldest first
+/// `zone` matches a zone id or display name (case-insensitive); `since` drops older plays
+pub fn read_history(zone: Option<&str>, since: Option<DateTime<Utc>>) -> Result<Vec<PlayRecord>, String> {
+    let path = crate::config::play_history_path();
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: PlayRecord = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(e) => {
+                log::warn!("Skipping bad play history line: {}", e);
+                continue;
+            }
+        };
+        if let Some(zone) = zone {
+            if record.zone_id != zone && !record.zone_name.eq_ignore_ascii_case(zone) {
+                continue;
+            }
+        }
+        if let Some(since) = since {
+            if record.started_at < since {
+                continue;
+            }
+        }
+        records.push(record);
+    }
+
+    Ok(records)
+}
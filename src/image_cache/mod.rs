@@ -0,0 +1,401 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::SystemTime;
+use serde::Deserialize;
+use tokio::sync::{oneshot, Mutex};
+
+/// Image data with content type
+#[derive(Clone, Debug)]
+pub struct ImageData {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Image encoding requested from Roon
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    #[serde(alias = "jpg")]
+    Jpeg,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+        }
+    }
+
+    pub fn to_roon(self) -> roon_api::image::Format {
+        match self {
+            ImageFormat::Jpeg => roon_api::image::Format::Jpeg,
+            ImageFormat::Png => roon_api::image::Format::Png,
+        }
+    }
+}
+
+/// How Roon scales an image to the requested size
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageScale {
+    #[default]
+    Fit, // Fit inside, keeping the aspect ratio
+    Fill, // Cover, keeping the aspect ratio
+    Stretch, // Exactly width x height
+}
+
+impl ImageScale {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageScale::Fit => "fit",
+            ImageScale::Fill => "fill",
+            ImageScale::Stretch => "stretch",
+        }
+    }
+
+    pub fn to_roon(self) -> roon_api::image::Scale {
+        match self {
+            ImageScale::Fit => roon_api::image::Scale::Fit,
+            ImageScale::Fill => roon_api::image::Scale::Fill,
+            ImageScale::Stretch => roon_api::image::Scale::Stretch,
+        }
+    }
+}
+
+/// Largest width or height that can be requested
+pub const MAX_IMAGE_SIZE: u32 = 4096;
+
+/// Size, scaling and format of a cached image; part of the cache key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImageSpec {
+    pub width: u32,
+    pub height: u32,
+    pub scale: ImageScale,
+    pub format: ImageFormat,
+}
+
+impl ImageSpec {
+    /// Sizes are clamped to 1..=MAX_IMAGE_SIZE
+    pub fn new(width: u32, height: u32, scale: ImageScale, format: ImageFormat) -> Self {
+        ImageSpec {
+            width: width.clamp(1, MAX_IMAGE_SIZE),
+            height: height.clamp(1, MAX_IMAGE_SIZE),
+            scale,
+            format,
+        }
+    }
+
+    /// Album art at the configured square size
+    pub fn album_art(size: u32) -> Self {
+        ImageSpec::new(size, size, ImageScale::Fit, ImageFormat::Jpeg)
+    }
+
+    /// Entity tag for HTTP caching; Roon image keys name immutable images,
+    /// so the key and spec identify the bytes
+    pub fn etag(&self, image_key: &str) -> String {
+        format!("\"{}\"", cache_key(image_key, self))
+    }
+}
+
+/// Cache key (and disk file name) for an image key at a size, scaling and format
+fn cache_key(image_key: &str, spec: &ImageSpec) -> String {
+    // Roon image keys are hex, but they end up in file names, so be safe
+    let safe_key: String = image_key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}_{}x{}_{}.{}", safe_key, spec.width, spec.height, spec.scale.name(), spec.format.extension())
+}
+
+/// In-memory tier: least recently used entries are dropped past the byte budget
+struct MemoryTier {
+    entries: HashMap<String, (ImageData, u64)>, // cache key -> (image, last use)
+    order: BTreeMap<u64, String>, // last use -> cache key, oldest first
+    clock: u64,
+    bytes: u64,
+    budget: u64,
+}
+
+impl MemoryTier {
+    fn get(&mut self, key: &str) -> Option<ImageData> {
+        self.clock += 1;
+        let clock = self.clock;
+        let (data, last_use) = self.entries.get_mut(key)?;
+        self.order.remove(last_use);
+        *last_use = clock;
+        self.order.insert(clock, key.to_string());
+        Some(data.clone())
+    }
+
+    fn insert(&mut self, key: String, data: ImageData) {
+        let size = data.data.len() as u64;
+        if size > self.budget {
+            return;
+        }
+        self.remove(&key);
+
+        while self.bytes + size > self.budget {
+            let Some((_, oldest)) = self.order.pop_first() else { break };
+            if let Some((evicted, _)) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.data.len() as u64;
+                log::debug!("Evicted image from memory cache: {}", oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.order.insert(self.clock, key.clone());
+        self.entries.insert(key, (data, self.clock));
+        self.bytes += size;
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((data, last_use)) = self.entries.remove(key) {
+            self.order.remove(&last_use);
+            self.bytes -= data.data.len() as u64;
+        }
+    }
+}
+
+/// On-disk tier in the state directory; files not used recently are deleted past the byte budget
+/// Its methods do blocking file I/O, so ImageCache calls them on the blocking pool
+struct DiskTier {
+    dir: PathBuf,
+    bytes: u64,
+    budget: u64,
+}
+
+impl DiskTier {
+    fn open(dir: PathBuf, budget: u64) -> Option<Self> {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            log::warn!("Image disk cache disabled, can't create {}: {}", dir.display(), e);
+            return None;
+        }
+        let bytes = Self::files(&dir).iter().map(|(_, size, _)| size).sum();
+        log::debug!("Image disk cache at {} holds {} bytes", dir.display(), bytes);
+        Some(DiskTier { dir, bytes, budget })
+    }
+
+    /// (path, size, last modified) of every cached file
+    fn files(dir: &PathBuf) -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+        entries.filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            Some((entry.path(), metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        }).collect()
+    }
+
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.dir.join(key);
+        let data = fs::read(&path).ok()?;
+        // Mark as recently used so eviction keeps it
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(data)
+    }
+
+    fn insert(&mut self, key: &str, data: &[u8]) {
+        let size = data.len() as u64;
+        if size > self.budget {
+            return;
+        }
+        let path = self.dir.join(key);
+        if let Err(e) = fs::write(&path, data) {
+            log::warn!("Failed to write {}: {}", path.display(), e);
+            return;
+        }
+        self.bytes += size;
+
+        if self.bytes > self.budget {
+            // Recount from the directory, then delete least recently used files
+            let mut files = Self::files(&self.dir);
+            self.bytes = files.iter().map(|(_, size, _)| size).sum();
+            files.sort_by_key(|(_, _, modified)| *modified);
+            for (file, size, _) in files {
+                if self.bytes <= self.budget {
+                    break;
+                }
+                if file == path {
+                    continue;
+                }
+                if fs::remove_file(&file).is_ok() {
+                    self.bytes -= size;
+                    log::debug!("Evicted image from disk cache: {}", file.display());
+                }
+            }
+        }
+    }
+}
+
+/// Album art cache: a bounded LRU in memory over a bounded directory on disk
+/// Entries are keyed by image key, size and format
+#[derive(Clone)]
+pub struct ImageCache {
+    memory: Arc<Mutex<MemoryTier>>,
+    disk: Option<Arc<StdMutex<DiskTier>>>, // Locked only on the blocking pool
+    pending: Arc<Mutex<HashMap<String, (String, ImageSpec)>>>, // request id -> (image key, spec)
+    waiters: Arc<Mutex<HashMap<String, Vec<oneshot::Sender<ImageData>>>>>, // cache key -> callers waiting for it
+}
+
+impl ImageCache {
+    /// Cache with the budgets from the [cache] config section
+    pub fn new() -> Self {
+        let config = &crate::config::get().cache;
+        let disk = if config.disk_mb > 0 {
+            DiskTier::open(crate::config::image_cache_dir(), config.disk_mb * 1024 * 1024)
+        } else {
+            None
+        };
+
+        ImageCache {
+            memory: Arc::new(Mutex::new(MemoryTier {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                clock: 0,
+                bytes: 0,
+                budget: config.memory_mb * 1024 * 1024,
+            })),
+            disk: disk.map(|disk| Arc::new(StdMutex::new(disk))),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            waiters: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Look up an image, from memory first, then disk (which promotes it to memory)
+    pub async fn get(&self, image_key: &str, spec: &ImageSpec) -> Option<ImageData> {
+        let key = cache_key(image_key, spec);
+        if let Some(data) = self.memory.lock().await.get(&key) {
+            return Some(data);
+        }
+
+        let disk_key = key.clone();
+        let data = self.with_disk(move |disk| disk.get(&disk_key)).await??;
+        let image = ImageData {
+            content_type: spec.format.content_type().to_string(),
+            data,
+        };
+        log::debug!("Image loaded from disk cache: {}", key);
+        self.memory.lock().await.insert(key, image.clone());
+        Some(image)
+    }
+
+    /// Whether an image is cached in either tier
+    pub async fn contains(&self, image_key: &str, spec: &ImageSpec) -> bool {
+        let key = cache_key(image_key, spec);
+        if self.memory.lock().await.entries.contains_key(&key) {
+            return true;
+        }
+        self.with_disk(move |disk| disk.dir.join(&key).is_file()).await.unwrap_or(false)
+    }
+
+    /// Run a disk tier operation on the blocking pool (None without a disk tier)
+    async fn with_disk<T: Send + 'static>(&self, op: impl FnOnce(&mut DiskTier) -> T + Send + 'static) -> Option<T> {
+        let disk = self.disk.clone()?;
+        tokio::task::spawn_blocking(move || disk.lock().ok().map(|mut disk| op(&mut disk)))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Store an image in both tiers and hand it to anyone waiting for it
+    pub async fn insert(&self, image_key: &str, spec: &ImageSpec, image: ImageData) {
+        let key = cache_key(image_key, spec);
+        if let Some(waiters) = self.waiters.lock().await.remove(&key) {
+            for waiter in waiters {
+                let _ = waiter.send(image.clone());
+            }
+        }
+        // Written in the background; until then the memory tier has it
+        if let Some(disk) = self.disk.clone() {
+            let (disk_key, data) = (key.clone(), image.data.clone());
+            tokio::task::spawn_blocking(move || {
+                if let Ok(mut disk) = disk.lock() {
+                    disk.insert(&disk_key, &data);
+                }
+            });
+        }
+        self.memory.lock().await.insert(key, image);
+    }
+
+    /// Send an image request to Roon (`send` returns its request id) and record what it asked for,
+    /// so the response is stored under that size and format
+    /// The pending lock is held while sending, so the response can't be handled before it is recorded
+    pub async fn request(&self, image_key: &str, spec: ImageSpec, send: impl Future<Output = Option<usize>>) -> Option<usize> {
+        let mut pending = self.pending.lock().await;
+        let request_id = send.await?;
+        pending.insert(request_id.to_string(), (image_key.to_string(), spec));
+        Some(request_id)
+    }
+
+    /// Whether a request for this image is already on its way
+    pub async fn is_pending(&self, image_key: &str, spec: &ImageSpec) -> bool {
+        self.pending.lock().await.values().any(|(key, pending)| key == image_key && pending == spec)
+    }
+
+    /// Receiver that gets the image when it is next inserted
+    pub async fn wait_for(&self, image_key: &str, spec: &ImageSpec) -> oneshot::Receiver<ImageData> {
+        let (tx, rx) = oneshot::channel();
+        let mut waiters = self.waiters.lock().await;
+        let key_waiters = waiters.entry(cache_key(image_key, spec)).or_default();
+        key_waiters.retain(|waiter| !waiter.is_closed()); // Drop callers that timed out
+        key_waiters.push(tx);
+        rx
+    }
+
+    /// Store an image response from Roon under the spec of the request it answers
+    pub async fn on_response(&self, request_id: Option<String>, image_key: String, format: ImageFormat, data: Vec<u8>) {
+        let request = match &request_id {
+            Some(request_id) => self.pending.lock().await.remove(request_id),
+            None => None,
+        };
+        let Some((requested_key, mut spec)) = request else {
+            log::debug!("Ignoring unrequested image: {} (request {:?})", image_key, request_id);
+            return;
+        };
+        if requested_key != image_key {
+            log::warn!("Ignoring image {} in response to a request for {}", image_key, requested_key);
+            return;
+        }
+
+        spec.format = format;
+        let image = ImageData {
+            content_type: format.content_type().to_string(),
+            data,
+        };
+        self.insert(&image_key, &spec, image).await;
+    }
+
+    /// Forget every request (e.g. when the Core connection is lost, as they will get no reply);
+    /// callers waiting for them are failed right away
+    pub async fn clear_pending(&self) {
+        self.pending.lock().await.clear();
+        self.waiters.lock().await.clear();
+    }
+
+    /// Forget a request Roon answered with an error (e.g. no such image); callers waiting
+    /// for it are failed right away unless the same image is still requested elsewhere
+    pub async fn on_error(&self, request_id: &str) {
+        let Some((image_key, spec)) = self.pending.lock().await.remove(request_id) else {
+            return;
+        };
+        log::debug!("Image request {} for {} failed", request_id, image_key);
+        if !self.is_pending(&image_key, &spec).await {
+            self.waiters.lock().await.remove(&cache_key(&image_key, &spec));
+        }
+    }
+}
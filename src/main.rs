@@ -4,9 +4,16 @@ mod roon;
 mod upnp;
 mod dcs;
 mod tui;
+mod settings;
+mod config;
+mod history;
+mod stats;
+mod image_cache;
+mod zone_keys;
 
 use clap::{Parser, Subcommand};
 use simplelog::*;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use roon::RoonClient;
@@ -23,9 +30,21 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// UPnP-only mode (don't connect to Roon)
+    /// UPnP-only mode (don't connect to Roon; overrides roon.upnp_only)
     #[arg(long, global = true)]
     upnp_only: bool,
+
+    /// Config file (default: ~/.config/roon-rd/config.toml, or $ROON_RD_CONFIG)
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Directory for Roon state and history (default: ~/.local/state/roon-rd, or $ROON_RD_STATE_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    state_dir: Option<PathBuf>,
+
+    /// Connect directly to a Roon Core (host or host:port) instead of discovering it (overrides roon.core)
+    #[arg(long, global = true, value_name = "HOST:PORT")]
+    core: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -38,9 +57,9 @@ enum Commands {
     },
     /// Start web server mode
     Server {
-        /// Port to listen on
-        #[arg(short, long, default_value = "3000")]
-        port: u16,
+        /// Port to listen on (overrides server.port in the config file and the Roon extension setting)
+        #[arg(short, long)]
+        port: Option<u16>,
     },
     /// Interactive mode - read commands from stdin
     Interactive,
@@ -93,15 +112,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ])?;
     }
 
+    // Set up the state directory before anything reads state or history
+    if let Some(dir) = &cli.state_dir {
+        config::set_state_dir(dir.clone());
+    }
+    match config::init_state_dir() {
+        Ok(dir) => log::debug!("Using state directory {}", dir.display()),
+        Err(e) => log::warn!("Failed to create state directory {}: {}", config::state_dir().display(), e),
+    }
+
+    // Load the config file; command-line flags override it
+    let port_flag = match &cli.command {
+        Commands::Server { port } => *port,
+        _ => None,
+    };
+    let mut app_config = config::AppConfig::load(cli.config.clone())?;
+    app_config.apply_flags(port_flag, cli.core.clone(), cli.upnp_only);
+    let upnp_only = app_config.roon.upnp_only;
+    config::init(app_config);
+
     // Create and initialize Roon client (unless upnp-only mode)
-    let client = if cli.upnp_only {
+    let client = if upnp_only {
         // Create a dummy client that won't be used
         None
     } else {
         log::info!("Initializing Roon Remote Display...");
         let mut roon_client = RoonClient::new()?;
-        roon_client.connect().await?;
-        Some(Arc::new(Mutex::new(roon_client)))
+        // --core or roon.core from the config file; the Roon core address setting only when neither is set
+        roon_client.set_core_address(config::get().roon.core.clone());
+        // A failed first attempt is retried by the supervisor like any lost connection
+        if let Err(e) = roon_client.connect().await {
+            log::warn!("{}", e);
+        }
+        roon_client.start_device_updates();
+        let roon_client = Arc::new(Mutex::new(roon_client));
+        RoonClient::supervise(roon_client.clone());
+        Some(roon_client)
     };
 
     // Handle commands
@@ -112,9 +158,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         Commands::Server { port } => {
             if let Some(client) = client {
+                // --port, then the Roon extension setting (which starts from server.port in the config file)
+                let port = match port {
+                    Some(port) => port,
+                    None => client.lock().await.get_settings().await.server_port,
+                };
                 server::start_server(client, port).await?;
             } else {
-                return Err("Server mode requires Roon connection. Remove --upnp-only flag.".into());
+                return Err("Server mode requires Roon connection. Remove --upnp-only flag (or roon.upnp_only in the config file).".into());
             }
         }
         Commands::Interactive => {
@@ -1,23 +1,42 @@
 use roon_api::{Info, RoonApi, CoreEvent, Services, Parsed};
-use roon_api::transport::{Transport, Zone, QueueItem, State};
-use roon_api::image::{Image, Args as ImageArgs, Scaling, Scale, Format};
-use roon_api::browse::{Browse, BrowseOpts, LoadOpts};
-use std::collections::HashMap;
+use roon_api::transport::{Transport, Zone, Output, QueueItem, QueueChange, QueueOperation, State, Settings as ZoneSettings, Repeat};
+use roon_api::image::{Image, Args as ImageArgs, Scaling};
+use roon_api::settings::Settings;
+use roon_api::browse::{Browse, BrowseOpts, LoadOpts, BrowseResult, LoadResult, Item as RoonBrowseItem, ItemHint};
+use crate::settings::{self, ExtensionSettings, ZoneDevice};
+use crate::history::PlayHistory;
+use crate::image_cache::{ImageCache, ImageData, ImageFormat, ImageSpec};
+use crate::zone_keys::ZoneKeys;
+use crate::dcs::{DcsDeviceState, DcsPoller};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::{RwLock, broadcast, Notify};
 
-/// Image data with content type
-#[derive(Clone, Debug)]
-pub struct ImageData {
-    pub content_type: String,
-    pub data: Vec<u8>,
+/// Output membership of a zone for WebSocket updates and HTTP responses
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct WsOutputData {
+    pub output_id: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub source_controls: Vec<WsSourceControl>,
+}
+
+/// Source control (e.g. an amp or DAC input) attached to an output
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct WsSourceControl {
+    pub control_key: String,
+    pub display_name: String,
+    pub status: String, // selected, deselected, standby or indeterminate
+    pub supports_standby: bool,
 }
 
 /// Simplified zone data for WebSocket updates and HTTP responses
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct WsZoneData {
     pub zone_id: String,
+    pub zone_key: Option<String>, // Stable roon-rd key, survives Core restarts and regrouping
     pub zone_name: String,
+    pub outputs: Vec<WsOutputData>,
     pub state: String,
     pub track: Option<String>,
     pub artist: Option<String>,
@@ -27,9 +46,35 @@ pub struct WsZoneData {
     pub image_key: Option<String>,
     pub artist_image_keys: Option<Vec<String>>,
     pub is_muted: Option<bool>,
-    pub dcs_format: Option<String>,
+    pub dcs_format: Option<String>, // Format reported by the zone's device (dCS or UPnP)
+    pub device_kind: Option<String>, // "dcs" or "upnp" when the zone maps to a device
     pub queue_items_remaining: i64,
     pub queue_time_remaining: i64,
+    pub shuffle: bool,
+    pub loop_mode: String,
+    pub auto_radio: bool,
+}
+
+/// Output registry entry for WebSocket updates and HTTP responses
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct OutputInfo {
+    pub output_id: String,
+    pub display_name: String,
+    pub zone_id: String,
+    pub volume: Option<OutputVolume>, // None for fixed-volume outputs
+    pub source_controls: Vec<WsSourceControl>,
+    pub can_group_with_output_ids: Vec<String>,
+}
+
+/// Volume capabilities and current level of an output
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct OutputVolume {
+    pub scale: String, // number, db or incremental
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub value: Option<f32>,
+    pub step: Option<f32>,
+    pub is_muted: Option<bool>,
 }
 
 /// Message types for WebSocket updates
@@ -45,7 +90,11 @@ pub enum WsMessage {
         raw_json: Option<String>,
     },
     #[serde(rename = "connection_changed")]
-    ConnectionChanged { connected: bool },
+    ConnectionChanged {
+        connected: bool,
+        #[serde(flatten)]
+        state: ConnectionState,
+    },
     #[serde(rename = "seek_updated")]
     SeekUpdated {
         zone_id: String,
@@ -56,48 +105,638 @@ pub enum WsMessage {
     QueueChanged {
         zone_id: String,
     },
+    #[serde(rename = "outputs_changed")]
+    OutputsChanged {
+        outputs: Vec<OutputInfo>,
+    },
+    #[serde(rename = "device_changed")]
+    DeviceChanged {
+        state: DcsDeviceState,
+    },
+    #[serde(rename = "artist_image_changed")]
+    ArtistImageChanged {
+        zone_id: String,
+        image_key: String,
+        index: usize,
+        count: usize,
+    },
+}
+
+/// A single entry in a browse list
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BrowseItem {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub item_key: Option<String>,
+    pub image_key: Option<String>,
+    pub hint: Option<String>, // action, action_list, list or header
+    pub input_prompt: Option<String>, // Set for items that take text input (e.g. Search)
+}
+
+/// One page of a browse list
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BrowseList {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub count: usize, // Total number of items in the list
+    pub offset: usize, // Offset of the first item in `items`
+    pub items: Vec<BrowseItem>,
+}
+
+/// Keeps a queue subscription open for every zone (TODO.md "Option A")
+///
+/// rust-roon-api only parses queue events for its most recent `subscribe_queue` call,
+/// so earlier subscriptions are tracked here by request ID and their `Changed`
+/// messages are parsed from the raw message. `subscribe_queue` doesn't return its
+/// request ID, so initial snapshots are matched to zones in subscription order (the
+/// Core answers requests in order); a snapshot that arrives after the wait timed out
+/// is still recorded for its zone.
+#[derive(Clone)]
+struct QueueTracker {
+    queues: Arc<RwLock<HashMap<String, Vec<QueueItem>>>>, // zone_id -> queue items
+    subscriptions: Arc<RwLock<HashMap<String, String>>>, // request_id -> zone_id
+    awaiting: Arc<RwLock<VecDeque<String>>>, // zones waiting for their initial snapshot, oldest first
+    subscribe_lock: Arc<tokio::sync::Mutex<()>>, // Serializes subscriptions
+    ready: Arc<Notify>, // Notifies when a snapshot arrives
+}
+
+impl QueueTracker {
+    fn new() -> Self {
+        QueueTracker {
+            queues: Arc::new(RwLock::new(HashMap::new())),
+            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            awaiting: Arc::new(RwLock::new(VecDeque::new())),
+            subscribe_lock: Arc::new(tokio::sync::Mutex::new(())),
+            ready: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Subscribed, or waiting for the snapshot of a subscription already sent
+    async fn is_subscribed(&self, zone_id: &str) -> bool {
+        self.subscriptions.read().await.values().any(|z| z == zone_id)
+            || self.awaiting.read().await.iter().any(|z| z == zone_id)
+    }
+
+    /// Subscribe to a zone's queue and wait (up to 2 seconds) for the snapshot
+    /// Returns immediately if the zone is already subscribed
+    async fn subscribe(&self, transport: &Transport, zone_id: &str) {
+        if self.is_subscribed(zone_id).await {
+            return;
+        }
+
+        let _guard = self.subscribe_lock.lock().await;
+        if self.is_subscribed(zone_id).await {
+            return;
+        }
+
+        self.awaiting.write().await.push_back(zone_id.to_string());
+        log::info!("Subscribing to queue for zone {}...", zone_id);
+        transport.subscribe_queue(zone_id, QUEUE_MAX_ITEMS).await;
+
+        // A late snapshot for an earlier zone also notifies, so check whose arrived
+        let snapshot = async {
+            loop {
+                let notified = self.ready.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if !self.awaiting.read().await.iter().any(|z| z == zone_id) {
+                    break;
+                }
+                notified.await;
+            }
+        };
+        let timeout = tokio::time::Duration::from_secs(2);
+        if tokio::time::timeout(timeout, snapshot).await.is_err() {
+            log::warn!("Timeout waiting for queue data for zone {}, keeping it when it arrives", zone_id);
+        }
+    }
+
+    /// Store an initial snapshot for the oldest subscription still waiting for one
+    async fn on_snapshot(&self, request_id: Option<String>, items: Vec<QueueItem>) {
+        let awaiting = self.awaiting.write().await.pop_front();
+        match (awaiting, request_id) {
+            (Some(zone_id), Some(request_id)) => {
+                log::info!("Queue snapshot for zone {} - {} items", zone_id, items.len());
+                self.subscriptions.write().await.insert(request_id, zone_id.clone());
+                self.queues.write().await.insert(zone_id, items);
+                self.ready.notify_waiters();
+            }
+            _ => log::warn!("Received queue snapshot that doesn't match a pending subscription"),
+        }
+    }
+
+    /// Apply queue changes for the subscription with this request ID
+    /// Returns the zone_id whose queue changed
+    async fn on_changes(&self, request_id: Option<String>, changes: Vec<QueueChange>) -> Option<String> {
+        let zone_id = self.subscriptions.read().await.get(&request_id?).cloned()?;
+
+        let mut queues = self.queues.write().await;
+        let queue = queues.entry(zone_id.clone()).or_default();
+        apply_queue_changes(queue, changes);
+        log::info!("Queue updated for zone {} - now has {} items", zone_id, queue.len());
+        Some(zone_id)
+    }
+
+    /// Apply a raw `Changed` message if it belongs to one of our queue subscriptions
+    /// Used for messages rust-roon-api didn't parse as `Parsed::QueueChanges` (see above)
+    /// Returns the zone_id whose queue changed
+    async fn on_raw_message(&self, raw_msg: &serde_json::Value) -> Option<String> {
+        if raw_msg["name"] != "Changed" {
+            return None;
+        }
+        let request_id = raw_request_id(raw_msg)?;
+        let zone_id = self.subscriptions.read().await.get(&request_id).cloned()?;
+
+        match serde_json::from_value(raw_msg["body"]["changes"].clone()) {
+            Ok(changes) => self.on_changes(Some(request_id), changes).await,
+            Err(e) => {
+                log::warn!("Failed to parse queue changes for zone {}: {}", zone_id, e);
+                None
+            }
+        }
+    }
+
+    /// Forget a zone's queue (the Core ends the subscription when the zone goes away)
+    async fn remove_zone(&self, zone_id: &str) {
+        self.queues.write().await.remove(zone_id);
+        self.subscriptions.write().await.retain(|_, z| z != zone_id);
+    }
+
+    /// Forget all subscriptions (on connection loss)
+    async fn clear(&self) {
+        self.queues.write().await.clear();
+        self.subscriptions.write().await.clear();
+        self.awaiting.write().await.clear();
+    }
+}
+
+/// Resolve a "host", "host:port", "[ipv6]" or "[ipv6]:port" core address to an IP and port
+async fn resolve_core_address(core_address: &str) -> Result<(std::net::IpAddr, String), String> {
+    if let Ok(addr) = core_address.parse::<std::net::SocketAddr>() {
+        return Ok((addr.ip(), addr.port().to_string()));
+    }
+    if let Some(ip) = core_address.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+        let ip: std::net::Ipv6Addr = ip.parse().map_err(|_| format!("invalid IPv6 address '{}'", ip))?;
+        return Ok((ip.into(), DEFAULT_CORE_PORT.to_string()));
+    }
+    if core_address.matches(':').count() > 1 {
+        return Err(format!("IPv6 addresses need brackets, e.g. [{}] or [{}]:{}", core_address, core_address, DEFAULT_CORE_PORT));
+    }
+
+    let (host, port) = match core_address.rsplit_once(':') {
+        Some((host, port)) => {
+            let port: u16 = port.parse().map_err(|_| format!("invalid port '{}'", port))?;
+            (host, port)
+        }
+        None => (core_address, DEFAULT_CORE_PORT),
+    };
+
+    let mut addrs = tokio::net::lookup_host((host, port)).await
+        .map_err(|e| format!("cannot resolve '{}': {}", host, e))?;
+    let addr = addrs.next().ok_or(format!("no address for '{}'", host))?;
+    Ok((addr.ip(), port.to_string()))
+}
+
+/// Request ID of a raw Roon message (sent as a number or string)
+fn raw_request_id(raw_msg: &serde_json::Value) -> Option<String> {
+    match &raw_msg["request_id"] {
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Apply insert/remove operations to a cached queue
+fn apply_queue_changes(queue: &mut Vec<QueueItem>, changes: Vec<QueueChange>) {
+    for change in changes {
+        log::debug!("  Operation: {:?} at index {}", change.operation, change.index);
+
+        match change.operation {
+            QueueOperation::Insert => {
+                if let Some(items) = change.items {
+                    let idx = change.index.min(queue.len());
+                    for (i, item) in items.into_iter().enumerate() {
+                        log::debug!("    Inserting: {} (id: {})", item.two_line.line1, item.queue_item_id);
+                        queue.insert(idx + i, item);
+                    }
+                }
+            }
+            QueueOperation::Remove => {
+                let count = change.count.unwrap_or(1);
+                let idx = change.index;
+                log::debug!("    Removing {} items at index {}", count, idx);
+                for _ in 0..count {
+                    if idx < queue.len() {
+                        queue.remove(idx);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Where the client is in connecting to the Roon Core
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConnectionState {
+    Disconnected, // Not started yet
+    Discovering, // Looking for (or dialing) the Core
+    AwaitingAuthorization, // Core found, extension not enabled in Roon yet
+    Connected,
+    Lost, // Connection dropped or failed, the supervisor will retry
+    BackingOff { attempt: u32, retry_in_secs: u64 },
+}
+
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        *self == ConnectionState::Connected
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectionState::Disconnected => "disconnected",
+            ConnectionState::Discovering => "discovering",
+            ConnectionState::AwaitingAuthorization => "awaiting_authorization",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Lost => "lost",
+            ConnectionState::BackingOff { .. } => "backing_off",
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionState::Disconnected => write!(f, "Not connected"),
+            ConnectionState::Discovering => write!(f, "Looking for Roon Core"),
+            ConnectionState::AwaitingAuthorization => write!(f, "Waiting for authorization"),
+            ConnectionState::Connected => write!(f, "Connected"),
+            ConnectionState::Lost => write!(f, "Connection lost"),
+            ConnectionState::BackingOff { attempt, retry_in_secs } => {
+                write!(f, "Reconnecting in {}s (attempt {})", retry_in_secs, attempt)
+            }
+        }
+    }
+}
+
+/// Shared connection state; every change is broadcast as ConnectionChanged
+#[derive(Clone)]
+struct ConnectionTracker {
+    state: Arc<RwLock<ConnectionState>>,
+    changed: Arc<Notify>, // Wakes the reconnect supervisor
+    ws_tx: broadcast::Sender<WsMessage>,
+}
+
+impl ConnectionTracker {
+    fn new(ws_tx: broadcast::Sender<WsMessage>) -> Self {
+        ConnectionTracker {
+            state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
+            changed: Arc::new(Notify::new()),
+            ws_tx,
+        }
+    }
+
+    async fn get(&self) -> ConnectionState {
+        self.state.read().await.clone()
+    }
+
+    async fn set(&self, new_state: ConnectionState) {
+        {
+            let mut state = self.state.write().await;
+            if *state == new_state {
+                return;
+            }
+            log::debug!("Connection state: {} -> {}", state.name(), new_state.name());
+            *state = new_state.clone();
+        }
+        self.changed.notify_waiters();
+        let _ = self.ws_tx.send(WsMessage::ConnectionChanged {
+            connected: new_state.is_connected(),
+            state: new_state,
+        });
+    }
+}
+
+/// Delay before reconnect attempt `attempt` (1-based): doubles each time up to the max
+fn backoff_secs(attempt: u32) -> u64 {
+    RECONNECT_INITIAL_SECS
+        .saturating_mul(1u64 << attempt.saturating_sub(1).min(16))
+        .min(RECONNECT_MAX_SECS)
+}
+
+/// Artist image currently shown for a zone by the rotation
+#[derive(Clone, Debug)]
+struct ArtistRotation {
+    image_keys: Vec<String>,
+    index: usize,
+}
+
+/// How the client reached the Roon Core
+#[derive(Clone, Debug)]
+pub enum ConnectionMethod {
+    Direct(String), // host:port as configured
+    Discovery,
+}
+
+impl ConnectionMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectionMethod::Direct(_) => "direct",
+            ConnectionMethod::Discovery => "discovery",
+        }
+    }
+
+    pub fn core_address(&self) -> Option<String> {
+        match self {
+            ConnectionMethod::Direct(address) => Some(address.clone()),
+            ConnectionMethod::Discovery => None,
+        }
+    }
 }
 
 /// Wrapper for Roon API client with state management
 pub struct RoonClient {
     api: RoonApi,
     zones: Arc<RwLock<HashMap<String, Zone>>>,
+    outputs: Arc<RwLock<HashMap<String, Output>>>, // output_id -> output
     zones_raw_json: Arc<RwLock<Option<String>>>, // Last raw JSON from Roon for zones_changed
-    queues: Arc<RwLock<HashMap<String, Vec<QueueItem>>>>, // zone_id -> queue items
-    active_queue_zone: Arc<RwLock<Option<String>>>, // zone_id of currently subscribed queue
-    queue_ready: Arc<Notify>, // Notifies when queue data arrives for active zone
-    connected: Arc<RwLock<bool>>,
+    queue_tracker: QueueTracker, // Live queues for every zone
+    play_history: PlayHistory, // Logs finished plays
+    zone_keys: ZoneKeys, // Stable zone keys
+    dcs_poller: DcsPoller, // Cached state of the dCS devices in use
+    connection: ConnectionTracker, // Connection state machine
     core_name: Arc<RwLock<Option<String>>>,
-    images: Arc<RwLock<HashMap<String, ImageData>>>,
+    images: ImageCache, // Album art by key, size and format
     image_service: Arc<RwLock<Option<Image>>>,
     transport_service: Arc<RwLock<Option<Transport>>>,
     browse_service: Arc<RwLock<Option<Browse>>>,
+    browse_result: Arc<RwLock<Option<BrowseResult>>>, // Last browse response for our session
+    load_result: Arc<RwLock<Option<LoadResult>>>, // Last load response for our session
+    browse_ready: Arc<Notify>, // Notifies when a browse response arrives
+    load_ready: Arc<Notify>, // Notifies when a load response arrives
+    settings: Arc<RwLock<ExtensionSettings>>, // Extension settings from Roon Settings > Extensions
+    core_address: Option<String>, // host:port from --core or the config file, skips discovery
+    connection_method: Option<ConnectionMethod>, // How the last connect() reached the Core
     ws_tx: broadcast::Sender<WsMessage>,
     pending_stops: Arc<tokio::sync::Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>, // zone_id -> delayed stop task
+    artist_rotation: Arc<RwLock<HashMap<String, ArtistRotation>>>, // zone_id -> artist image shown
 }
 
-const CONFIG_PATH: &str = "roon-rd-config.json";
+/// Resolve a zone reference to a zone id, trying in order: an exact zone id, a stable zone key,
+/// a display name, an alias from the [aliases] config section, then a unique case-insensitive name prefix
+/// `keys` maps zone ids to their stable keys
+fn resolve_zone_in(zones: &HashMap<String, Zone>, keys: &HashMap<String, String>, zone: &str) -> Result<String, String> {
+    let zone = zone.trim();
+    if zone.is_empty() {
+        // Would otherwise be a prefix of every zone name
+        return Err("No zone given".to_string());
+    }
+    let by_key = |key: &str| keys.iter()
+        .find(|(zone_id, k)| k.eq_ignore_ascii_case(key) && zones.contains_key(*zone_id))
+        .map(|(zone_id, _)| zone_id.clone());
+    let by_name = |name: &str| zones.values().find(|z| z.display_name.eq_ignore_ascii_case(name)).map(|z| z.zone_id.clone());
+
+    if zones.contains_key(zone) {
+        return Ok(zone.to_string());
+    }
+    if let Some(zone_id) = by_key(zone).or_else(|| by_name(zone)) {
+        return Ok(zone_id);
+    }
+
+    let aliases = &crate::config::get().aliases;
+    if let Some((_, target)) = aliases.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(zone)) {
+        if zones.contains_key(target) {
+            return Ok(target.clone());
+        }
+        return by_key(target).or_else(|| by_name(target)).ok_or_else(|| format!("Alias '{}' refers to '{}', which is not a current zone", zone, target));
+    }
+
+    let prefix = zone.to_lowercase();
+    let mut matches: Vec<&Zone> = zones.values()
+        .filter(|z| z.display_name.to_lowercase().starts_with(&prefix))
+        .collect();
+    matches.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    match matches.as_slice() {
+        [only] => Ok(only.zone_id.clone()),
+        [] => {
+            let mut names: Vec<&str> = zones.values().map(|z| z.display_name.as_str()).collect();
+            names.sort();
+            if names.is_empty() {
+                Err(format!("Zone not found: '{}' (no zones available)", zone))
+            } else {
+                Err(format!("Zone not found: '{}'. Zones: {}", zone, names.join(", ")))
+            }
+        }
+        _ => {
+            let names: Vec<&str> = matches.iter().map(|z| z.display_name.as_str()).collect();
+            Err(format!("Zone '{}' is ambiguous, it matches: {}", zone, names.join(", ")))
+        }
+    }
+}
+
+/// Roon state file (token, paired core, extension settings) in the state directory
+fn config_path() -> String {
+    crate::config::roon_state_path().to_string_lossy().into_owned()
+}
+
+/// First reconnect delay; doubles on each failed attempt
+const RECONNECT_INITIAL_SECS: u64 = 1;
+
+/// Longest delay between reconnect attempts
+const RECONNECT_MAX_SECS: u64 = 60;
+
+/// Default Roon Core WebSocket port when --core has no port
+const DEFAULT_CORE_PORT: u16 = 9330;
+
+/// Maximum number of queue items Roon sends per zone
+const QUEUE_MAX_ITEMS: u32 = 50;
+
+/// Browse session key, so our navigation doesn't disturb other Roon remotes
+const BROWSE_SESSION_KEY: &str = "roon-rd";
+
+/// Seconds to wait for a browse or load response from the Core
+const BROWSE_TIMEOUT_SECS: u64 = 5;
+
+/// Number of items loaded per browse page
+pub const BROWSE_PAGE_SIZE: usize = 100;
+
+/// Shortcut names for common places in the browse hierarchy
+fn hierarchy_path(hierarchy: &str) -> &str {
+    match hierarchy {
+        "albums" => "Library/Albums",
+        "artists" => "Library/Artists",
+        "tracks" => "Library/Tracks",
+        "composers" => "Library/Composers",
+        "playlists" => "Playlists",
+        "genres" => "Genres",
+        "radio" | "internet_radio" => "My Live Radio",
+        other => other,
+    }
+}
+
+/// Titles Roon uses for each play action in an item's action list
+pub fn play_action_titles(action: &str) -> Option<&'static [&'static str]> {
+    match action {
+        "now" | "play" => Some(&["Play Now", "Play"]),
+        "queue" => Some(&["Queue", "Add to Queue"]),
+        "next" => Some(&["Add Next", "Play Next"]),
+        "radio" => Some(&["Start Radio"]),
+        _ => None,
+    }
+}
+
+fn item_hint_name(hint: &ItemHint) -> &'static str {
+    match hint {
+        ItemHint::Action => "action",
+        ItemHint::ActionList => "action_list",
+        ItemHint::List => "list",
+        ItemHint::Header => "header",
+    }
+}
 
-/// Delay in milliseconds to wait for dCS device to update after a zone change
-/// This gives the dCS device time to process the new stream before browsers fetch the format
-const DCS_UPDATE_DELAY_MS: u64 = 200;
+impl From<&RoonBrowseItem> for BrowseItem {
+    fn from(item: &RoonBrowseItem) -> Self {
+        BrowseItem {
+            title: item.title.clone(),
+            subtitle: item.subtitle.clone(),
+            item_key: item.item_key.clone(),
+            image_key: item.image_key.clone(),
+            hint: item.hint.as_ref().map(|h| item_hint_name(h).to_string()),
+            input_prompt: item.input_prompt.as_ref().map(|p| p.prompt.clone()),
+        }
+    }
+}
+
+/// All member outputs of a zone (a grouped zone has more than one)
+pub fn zone_outputs(zone: &Zone) -> Vec<WsOutputData> {
+    zone.outputs.iter()
+        .map(|output| WsOutputData {
+            output_id: output.output_id.clone(),
+            display_name: output.display_name.clone(),
+            source_controls: source_controls_info(output),
+        })
+        .collect()
+}
+
+fn source_controls_info(output: &Output) -> Vec<WsSourceControl> {
+    output.source_controls.iter()
+        .flatten()
+        .map(|sc| WsSourceControl {
+            control_key: sc.control_key.clone(),
+            display_name: sc.display_name.clone(),
+            status: source_status_name(&sc.status).to_string(),
+            supports_standby: sc.supports_standby,
+        })
+        .collect()
+}
+
+fn output_info(output: &Output) -> OutputInfo {
+    OutputInfo {
+        output_id: output.output_id.clone(),
+        display_name: output.display_name.clone(),
+        zone_id: output.zone_id.clone(),
+        volume: output.volume.as_ref().map(|v| OutputVolume {
+            scale: format!("{:?}", v.scale).to_lowercase(),
+            min: v.min,
+            max: v.max,
+            value: v.value,
+            step: v.step,
+            is_muted: v.is_muted,
+        }),
+        source_controls: source_controls_info(output),
+        can_group_with_output_ids: output.can_group_with_output_ids.clone(),
+    }
+}
+
+/// Output registry snapshot, sorted by display name
+async fn outputs_snapshot(outputs: &RwLock<HashMap<String, Output>>) -> Vec<OutputInfo> {
+    let mut list: Vec<OutputInfo> = outputs.read().await.values().map(output_info).collect();
+    list.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    list
+}
+
+/// Short name for a source control status, as used by the CLI and HTTP API
+fn source_status_name(status: &roon_api::transport::Status) -> &'static str {
+    use roon_api::transport::Status;
+    match status {
+        Status::Selected => "selected",
+        Status::Deselected => "deselected",
+        Status::Standby => "standby",
+        Status::Indeterminate => "indeterminate",
+    }
+}
+
+/// Short name for a zone's loop setting, as used by the CLI and HTTP API
+pub fn loop_mode_name(repeat: &Repeat) -> &'static str {
+    match repeat {
+        Repeat::Disabled => "off",
+        Repeat::Loop => "all",
+        Repeat::LoopOne => "one",
+        Repeat::Next => "next",
+    }
+}
 
-/// Delay in milliseconds before broadcasting a Stopped state
-/// This prevents UI flickering during track transitions (Playing -> Stopped -> Loading -> Playing)
-/// If another event arrives within this window, the stop is cancelled
-const STOP_BROADCAST_DELAY_MS: u64 = 500;
+/// Format ("96 kHz 24 bit") currently reported by a zone's device
+async fn device_format(device: &ZoneDevice, zone_name: &str, dcs_poller: &DcsPoller) -> Option<String> {
+    use crate::upnp;
+
+    match device {
+        ZoneDevice::Dcs(host) => {
+            // Read from the poller cache; empty until the first poll of the device completes
+            let format = dcs_poller.get(host).await.and_then(|state| state.format);
+            log::debug!("dCS format for {} from {}: {:?}", zone_name, host, format);
+            format
+        }
+        ZoneDevice::Upnp(location) => {
+            match upnp::get_position_info(location).await {
+                Ok(position) => {
+                    let format = upnp::parse_audio_format(&position.track_metadata)?;
+                    let freq: u32 = format.sample_rate?.parse().ok()?;
+                    let bits: u32 = format.bits_per_sample?.parse().ok().filter(|bits| *bits > 0)?;
+                    let sample_rate_str = if freq >= 1000 {
+                        format!("{} kHz", freq / 1000)
+                    } else {
+                        format!("{} Hz", freq)
+                    };
+                    let format_str = format!("{} {} bit", sample_rate_str, bits);
+                    log::debug!("UPnP format for {}: {}", zone_name, format_str);
+                    Some(format_str)
+                }
+                Err(e) => {
+                    log::warn!("Failed to get UPnP position info for {}: {}", zone_name, e);
+                    None
+                }
+            }
+        }
+        ZoneDevice::None => None,
+    }
+}
 
 /// Build WebSocket zone data from zones Arc (standalone function for use in event handlers)
 /// Returns both the simplified WsZoneData, the raw Zones from Roon, and the raw JSON string
-async fn build_ws_zone_data_from_zones(zones: Arc<RwLock<HashMap<String, Zone>>>, zones_raw_json: Arc<RwLock<Option<String>>>) -> (Vec<WsZoneData>, Vec<Zone>, Option<String>) {
-    use crate::dcs;
-
+async fn build_ws_zone_data_from_zones(zones: Arc<RwLock<HashMap<String, Zone>>>, zones_raw_json: Arc<RwLock<Option<String>>>, settings: Arc<RwLock<ExtensionSettings>>, zone_keys: ZoneKeys, dcs_poller: DcsPoller) -> (Vec<WsZoneData>, Vec<Zone>, Option<String>) {
+    let settings = settings.read().await.clone();
+    let keys = zone_keys.snapshot().await;
     let zones_vec: Vec<Zone> = zones.read().await.values().cloned().collect();
     let raw_zones = zones_vec.clone();  // Keep a copy of raw zones
     log::debug!("build_ws_zone_data_from_zones: Processing {} zones", zones_vec.len());
 
+    // Keep a poller running for every dCS device in use
+    let mut dcs_hosts: Vec<String> = zones_vec.iter()
+        .filter_map(|zone| settings.device_for_zone(zone)?.dcs_host().map(|host| host.to_string()))
+        .collect();
+    dcs_hosts.sort();
+    dcs_hosts.dedup();
+    for host in &dcs_hosts {
+        dcs_poller.watch(host).await;
+    }
+    dcs_poller.retain(&dcs_hosts).await;
+
     // Process all zones in parallel
     let zone_futures: Vec<_> = zones_vec.into_iter().map(|zone| {
+        let device = settings.device_for_zone(&zone);
+        let zone_key = keys.get(&zone.zone_id).cloned();
+        let dcs_poller = dcs_poller.clone();
         async move {
             let zone_id = zone.zone_id.clone();
             let zone_name = zone.display_name.clone();
@@ -105,62 +744,17 @@ async fn build_ws_zone_data_from_zones(zones: Arc<RwLock<HashMap<String, Zone>>>
 
             log::debug!("Processing zone: {} ({}), state: {}", zone_name, zone_id, zone_state);
 
-            // Fetch dCS format on-demand if this is a dCS Vivaldi zone in Playing state
-            let dcs_format = if zone.display_name.starts_with("dCS Vivaldi")
-                && format!("{:?}", zone.state).to_lowercase() == "playing" {
-
-                log::debug!("Zone {} is dCS Vivaldi in Playing state, fetching format...", zone_name);
-
-                match dcs::get_playback_info("dcs-vivaldi.local").await {
-                    Ok(playback_info) => {
-                        log::debug!("dCS playback info retrieved for {}: {:?}", zone_name, playback_info);
-                        // Extract format from audio_format field
-                        if let Some(audio_format) = playback_info.audio_format {
-                            // Only return format if bits_per_sample is valid (non-zero)
-                            if let Some(bits) = audio_format.bits_per_sample {
-                                if bits > 0 {
-                                    let sample_rate_str = if let Some(freq) = audio_format.sample_frequency {
-                                        if freq >= 1000 {
-                                            format!("{} kHz", freq / 1000)
-                                        } else {
-                                            format!("{} Hz", freq)
-                                        }
-                                    } else {
-                                        String::new()
-                                    };
-
-                                    let bit_depth_str = format!("{} bit", bits);
-
-                                    if !sample_rate_str.is_empty() {
-                                        let format_str = format!("{} {}", sample_rate_str, bit_depth_str);
-                                        log::debug!("dCS format for {}: {}", zone_name, format_str);
-                                        Some(format_str)
-                                    } else {
-                                        log::debug!("dCS format missing sample rate for {}", zone_name);
-                                        None
-                                    }
-                                } else {
-                                    log::debug!("dCS format has bits_per_sample=0 for {}, not displaying", zone_name);
-                                    None
-                                }
-                            } else {
-                                log::debug!("dCS format missing bits_per_sample for {}", zone_name);
-                                None
-                            }
-                        } else {
-                            log::debug!("dCS playback info missing audio_format for {}", zone_name);
-                            None
-                        }
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to get dCS playback info for {}: {}", zone_name, e);
-                        None
-                    }
+            // Fetch the format on-demand if this zone has a device and is in Playing state
+            let dcs_format = match device.as_ref().filter(|_| zone.state == State::Playing) {
+                Some(device) => {
+                    log::debug!("Zone {} has device {} in Playing state, fetching format...", zone_name, device);
+                    device_format(device, &zone_name, &dcs_poller).await
+                }
+                None => {
+                    log::debug!("Zone {} not eligible for device format (name: {}, state: {})",
+                               zone_id, zone.display_name, format!("{:?}", zone.state));
+                    None
                 }
-            } else {
-                log::debug!("Zone {} not eligible for dCS format (name: {}, state: {})",
-                           zone_id, zone.display_name, format!("{:?}", zone.state));
-                None
             };
 
             // Extract track info if available
@@ -211,7 +805,9 @@ async fn build_ws_zone_data_from_zones(zones: Arc<RwLock<HashMap<String, Zone>>>
 
             let ws_data = WsZoneData {
                 zone_id: zone.zone_id.clone(),
+                zone_key,
                 zone_name: zone_name.clone(),
+                outputs: zone_outputs(&zone),
                 state: format!("{:?}", zone.state),
                 track: track.clone(),
                 artist,
@@ -222,8 +818,12 @@ async fn build_ws_zone_data_from_zones(zones: Arc<RwLock<HashMap<String, Zone>>>
                 artist_image_keys,
                 is_muted,
                 dcs_format: dcs_format.clone(),
+                device_kind: device.as_ref().map(|device| device.kind().to_string()),
                 queue_items_remaining: zone.queue_items_remaining,
                 queue_time_remaining: zone.queue_time_remaining,
+                shuffle: zone.settings.shuffle,
+                loop_mode: loop_mode_name(&zone.settings.repeat).to_string(),
+                auto_radio: zone.settings.auto_radio,
             };
 
             log::debug!("Built WsZoneData for {}: track={:?}, dcs_format={:?}",
@@ -281,27 +881,59 @@ impl RoonClient {
         );
         let api = RoonApi::new(info);
 
-        // Create broadcast channel for WebSocket updates (capacity of 100 messages)
-        let (ws_tx, _) = broadcast::channel(100);
+        // Create broadcast channel for WebSocket updates
+        let (ws_tx, _) = broadcast::channel(crate::config::get().server.broadcast_capacity);
 
         Ok(RoonClient {
             api,
             zones: Arc::new(RwLock::new(HashMap::new())),
+            outputs: Arc::new(RwLock::new(HashMap::new())),
             zones_raw_json: Arc::new(RwLock::new(None)),
-            queues: Arc::new(RwLock::new(HashMap::new())),
-            active_queue_zone: Arc::new(RwLock::new(None)),
-            queue_ready: Arc::new(Notify::new()),
-            connected: Arc::new(RwLock::new(false)),
+            queue_tracker: QueueTracker::new(),
+            play_history: PlayHistory::new(),
+            zone_keys: ZoneKeys::load(),
+            dcs_poller: DcsPoller::new(ws_tx.clone()),
+            connection: ConnectionTracker::new(ws_tx.clone()),
             core_name: Arc::new(RwLock::new(None)),
-            images: Arc::new(RwLock::new(HashMap::new())),
+            images: ImageCache::new(),
             image_service: Arc::new(RwLock::new(None)),
             transport_service: Arc::new(RwLock::new(None)),
             browse_service: Arc::new(RwLock::new(None)),
+            browse_result: Arc::new(RwLock::new(None)),
+            load_result: Arc::new(RwLock::new(None)),
+            browse_ready: Arc::new(Notify::new()),
+            load_ready: Arc::new(Notify::new()),
+            settings: Arc::new(RwLock::new(Self::load_settings())),
+            core_address: None,
+            connection_method: None,
             ws_tx,
             pending_stops: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            artist_rotation: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
+    /// Load the extension settings: the fields saved from Roon next to the Roon state,
+    /// layered over the config file (serde fills missing fields from the default)
+    /// Usable without a connection, e.g. in --upnp-only mode
+    pub fn load_settings() -> ExtensionSettings {
+        serde_json::from_value(RoonApi::load_config(&config_path(), "settings")).unwrap_or_default()
+    }
+
+    /// Get the current extension settings
+    pub async fn get_settings(&self) -> ExtensionSettings {
+        self.settings.read().await.clone()
+    }
+
+    /// Connect directly to this core (host or host:port) instead of discovering it
+    pub fn set_core_address(&mut self, core_address: Option<String>) {
+        self.core_address = core_address;
+    }
+
+    /// How the last connection attempt reached the Core
+    pub fn get_connection_method(&self) -> Option<ConnectionMethod> {
+        self.connection_method.clone()
+    }
+
     /// Get a WebSocket subscriber
     pub fn subscribe_ws(&self) -> broadcast::Receiver<WsMessage> {
         self.ws_tx.subscribe()
@@ -313,46 +945,142 @@ impl RoonClient {
         self.connect().await
     }
 
-    /// Start the Roon API connection
-    pub async fn connect(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        log::info!("Connecting to Roon Core...");
+    /// Reconnect automatically whenever the connection is lost or fails, with exponential backoff
+    /// Runs until the process exits; a manual reconnect during a backoff wins over the retry
+    pub fn supervise(client: Arc<tokio::sync::Mutex<RoonClient>>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let connection = client.lock().await.connection.clone();
+            let mut attempt: u32 = 0;
+
+            loop {
+                // Wait for the connection to drop
+                loop {
+                    let notified = connection.changed.notified();
+                    match connection.get().await {
+                        ConnectionState::Lost => break,
+                        ConnectionState::Connected => attempt = 0,
+                        _ => {}
+                    }
+                    notified.await;
+                }
+
+                attempt = attempt.saturating_add(1);
+                let retry_in_secs = backoff_secs(attempt);
+                log::info!("Reconnecting to Roon Core in {}s (attempt {})", retry_in_secs, attempt);
+                connection.set(ConnectionState::BackingOff { attempt, retry_in_secs }).await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(retry_in_secs)).await;
+
+                // Someone reconnected by hand while we waited
+                if !matches!(connection.get().await, ConnectionState::BackingOff { .. }) {
+                    continue;
+                }
+
+                // connect() marks the connection lost again on failure, which loops us back here
+                if let Err(e) = client.lock().await.connect().await {
+                    log::warn!("Reconnect attempt {} failed: {}", attempt, e);
+                }
+            }
+        })
+    }
+
+    /// Services we provide (settings) and request (transport, image, browse) from the Core
+    fn roon_services(&self) -> (HashMap<String, roon_api::Svc>, Option<Vec<Services>>) {
+        let mut provided: HashMap<String, roon_api::Svc> = HashMap::new();
 
-        // Prepare for connection
-        let provided: HashMap<String, roon_api::Svc> = HashMap::new();
+        // Provide the settings service so the extension can be configured from Roon
+        let (settings_svc, settings_service) = Settings::new(&self.api, Box::new(settings::make_layout));
+        provided.insert(roon_api::settings::SVCNAME.to_owned(), settings_svc);
 
-        // Request transport, image, and browse services
+        // Request transport, image, and browse services, and provide settings
         let services = Some(vec![
             Services::Transport(Transport::new()),
             Services::Image(Image::new()),
             Services::Browse(Browse::new()),
+            Services::Settings(settings_service),
         ]);
 
+        (provided, services)
+    }
+
+    /// Core address for a direct connection: --core or the config file, then the core address setting
+    async fn effective_core_address(&self) -> Option<String> {
+        if self.core_address.is_some() {
+            return self.core_address.clone();
+        }
+        let setting = self.settings.read().await.core_address.trim().to_string();
+        if setting.is_empty() { None } else { Some(setting) }
+    }
+
+    /// Start the Roon API connection
+    pub async fn connect(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        log::info!("Connecting to Roon Core...");
+
         // Get state callback - load from config file
         let get_roon_state = || {
-            RoonApi::load_roon_state(CONFIG_PATH)
+            RoonApi::load_roon_state(&config_path())
         };
 
         // Clone Arc references for the handler
         let zones = self.zones.clone();
+        let outputs = self.outputs.clone();
         let zones_raw_json = self.zones_raw_json.clone();
-        let queues = self.queues.clone();
-        let active_queue_zone = self.active_queue_zone.clone();
-        let queue_ready = self.queue_ready.clone();
-        let connected = self.connected.clone();
+        let queue_tracker = self.queue_tracker.clone();
+        let play_history = self.play_history.clone();
+        let zone_keys = self.zone_keys.clone();
+        let dcs_poller = self.dcs_poller.clone();
+        let connection = self.connection.clone();
         let core_name = self.core_name.clone();
         let images = self.images.clone();
         let image_service = self.image_service.clone();
         let transport_service = self.transport_service.clone();
         let browse_service = self.browse_service.clone();
+        let browse_result = self.browse_result.clone();
+        let load_result = self.load_result.clone();
+        let browse_ready = self.browse_ready.clone();
+        let load_ready = self.load_ready.clone();
+        let extension_settings = self.settings.clone();
         let ws_tx = self.ws_tx.clone();
         let pending_stops = self.pending_stops.clone();
 
-        // Start discovery
-        let result = self.api.start_discovery(
-            Box::new(get_roon_state),
-            provided,
-            services,
-        ).await;
+        self.connection.set(ConnectionState::Discovering).await;
+
+        // Connect directly to a known core if one is configured, falling back to discovery
+        let mut result = None;
+        self.connection_method = None;
+        if let Some(core_address) = self.effective_core_address().await {
+            match resolve_core_address(&core_address).await {
+                Ok((ip, port)) => {
+                    log::info!("Connecting directly to Roon Core at {}:{}...", ip, port);
+                    let (provided, services) = self.roon_services();
+                    result = self.api.ws_connect(
+                        Box::new(get_roon_state),
+                        provided,
+                        services,
+                        &ip,
+                        &port,
+                    ).await;
+                    if result.is_some() {
+                        self.connection_method = Some(ConnectionMethod::Direct(core_address));
+                    } else {
+                        log::warn!("Direct connection to {} failed, falling back to discovery", core_address);
+                    }
+                }
+                Err(e) => log::warn!("Invalid core address '{}': {}, falling back to discovery", core_address, e),
+            }
+        }
+
+        if result.is_none() {
+            // Start discovery
+            let (provided, services) = self.roon_services();
+            result = self.api.start_discovery(
+                Box::new(get_roon_state),
+                provided,
+                services,
+            ).await;
+            if result.is_some() {
+                self.connection_method = Some(ConnectionMethod::Discovery);
+            }
+        }
 
         if let Some((mut handlers, mut core_rx)) = result {
             log::info!("Roon API started. Please authorize this extension in Roon Settings > Extensions");
@@ -366,21 +1094,22 @@ impl RoonClient {
                             if let Some(token) = token {
                                 log::debug!("Using existing token: {}", token);
                             }
+                            // Registered follows once the extension is enabled in Roon
+                            connection.set(ConnectionState::AwaitingAuthorization).await;
                         }
                         CoreEvent::Registered(mut core, _token) => {
                             log::info!("Registered with Roon Core: {}, version {}", core.display_name, core.display_version);
 
-                            // Update connection state
-                            *connected.write().await = true;
+                            // Update and broadcast connection state
                             *core_name.write().await = Some(core.display_name.clone());
-
-                            // Broadcast connection change
-                            let _ = ws_tx.send(WsMessage::ConnectionChanged { connected: true });
+                            connection.set(ConnectionState::Connected).await;
 
                             // Subscribe to zone updates if we have transport service
                             if let Some(transport) = core.get_transport() {
                                 log::info!("Subscribing to zone updates...");
                                 transport.subscribe_zones().await;
+                                log::info!("Subscribing to output updates...");
+                                transport.subscribe_outputs().await;
                                 *transport_service.write().await = Some(transport.clone());
                             }
 
@@ -398,26 +1127,58 @@ impl RoonClient {
                         }
                         CoreEvent::Lost(core) => {
                             log::warn!("Lost connection to Roon Core: {}, version {}", core.display_name, core.display_version);
-                            *connected.write().await = false;
                             *core_name.write().await = None;
                             zones.write().await.clear();
+                            outputs.write().await.clear();
                             *image_service.write().await = None;
                             *transport_service.write().await = None;
                             *browse_service.write().await = None;
+                            queue_tracker.clear().await;
+                            play_history.finish_all().await;
+                            zone_keys.clear().await;
+                            images.clear_pending().await;
 
-                            // Broadcast connection change
-                            let _ = ws_tx.send(WsMessage::ConnectionChanged { connected: false });
+                            // Broadcast connection change; the supervisor reconnects
+                            connection.set(ConnectionState::Lost).await;
                         }
                         CoreEvent::None => {}
                     }
 
                     // Handle messages via Parsed enum
                     if let Some((raw_msg, parsed)) = msg {
-                        match parsed {
-                            Parsed::RoonState(roon_state) => {
-                                // Save state to persist authorization token
-                                if let Err(e) = RoonApi::save_roon_state(CONFIG_PATH, roon_state) {
-                                    log::error!("Failed to save Roon state: {}", e);
+                        // Queue changes for subscriptions rust-roon-api doesn't parse (see QueueTracker)
+                        if !matches!(parsed, Parsed::QueueChanges(_)) {
+                            if let Some(zone_id) = queue_tracker.on_raw_message(&raw_msg).await {
+                                let _ = ws_tx.send(WsMessage::QueueChanged { zone_id });
+                            }
+                        }
+
+                        // A failed image request gets an error reply (e.g. NotFound) instead of an image
+                        if raw_msg["name"] != "Success" {
+                            if let Some(request_id) = raw_request_id(&raw_msg) {
+                                images.on_error(&request_id).await;
+                            }
+                        }
+
+                        match parsed {
+                            Parsed::RoonState(roon_state) => {
+                                // Save state to persist authorization token
+                                if let Err(e) = RoonApi::save_roon_state(&config_path(), roon_state) {
+                                    log::error!("Failed to save Roon state: {}", e);
+                                }
+                            }
+                            Parsed::SettingsSaved(value) => {
+                                log::info!("Extension settings saved from Roon");
+                                match serde_json::from_value::<ExtensionSettings>(value) {
+                                    Ok(new_settings) => {
+                                        // Keep only what was changed in Roon, so the config file still applies to the rest
+                                        let overrides = new_settings.overrides();
+                                        *extension_settings.write().await = new_settings;
+                                        if let Err(e) = RoonApi::save_config(&config_path(), "settings", overrides) {
+                                            log::error!("Failed to save extension settings: {}", e);
+                                        }
+                                    }
+                                    Err(e) => log::error!("Invalid extension settings from Roon: {}", e),
                                 }
                             }
                             Parsed::Zones(zones_changed) => {
@@ -432,14 +1193,35 @@ impl RoonClient {
 
                                 // Collect image keys to request
                                 let mut image_keys_to_request = Vec::new();
+                                let art_spec = ImageSpec::album_art(extension_settings.read().await.album_art_size);
+
+                                // Zones we haven't seen yet get a queue subscription
+                                let new_zone_ids: Vec<String> = zones_changed.iter()
+                                    .filter(|zone| !zone_map.contains_key(&zone.zone_id))
+                                    .map(|zone| zone.zone_id.clone())
+                                    .collect();
+                                if !new_zone_ids.is_empty() {
+                                    let transport = transport_service.read().await.clone();
+                                    if let Some(transport) = transport {
+                                        let tracker = queue_tracker.clone();
+                                        tokio::spawn(async move {
+                                            for zone_id in new_zone_ids {
+                                                tracker.subscribe(&transport, &zone_id).await;
+                                            }
+                                        });
+                                    }
+                                }
 
                                 // Update zones that changed
                                 for zone in zones_changed {
+                                    play_history.on_zone(&zone).await;
+                                    zone_keys.update(&zone).await;
+
                                     // If the zone has now_playing with an image_key, queue it for download
                                     if let Some(ref now_playing) = zone.now_playing {
                                         if let Some(ref image_key) = now_playing.image_key {
                                             // Check if we don't already have this image cached
-                                            if !images.read().await.contains_key(image_key) {
+                                            if !images.contains(image_key, &art_spec).await {
                                                 image_keys_to_request.push(image_key.clone());
                                             }
                                         }
@@ -456,9 +1238,9 @@ impl RoonClient {
                                     if let Some(img) = img_svc.as_ref() {
                                         for image_key in image_keys_to_request {
                                             log::info!("Proactively requesting album art: {}", image_key);
-                                            let scaling = Scaling::new(Scale::Fit, 300, 300);
-                                            let args = ImageArgs::new(Some(scaling), Some(Format::Jpeg));
-                                            img.get_image(&image_key, args).await;
+                                            let scaling = Scaling::new(art_spec.scale.to_roon(), art_spec.width, art_spec.height);
+                                            let args = ImageArgs::new(Some(scaling), Some(art_spec.format.to_roon()));
+                                            images.request(&image_key, art_spec, img.get_image(&image_key, args)).await;
                                         }
                                     }
                                 }
@@ -469,11 +1251,18 @@ impl RoonClient {
                                 // Categorize zones by state
                                 let mut has_stopped_zones = Vec::new();
                                 let mut has_non_stop_zones = Vec::new();
-                                let has_dcs_playing_zones: Vec<_> = zones_snapshot.iter()
-                                    .filter(|(_, zone)| zone.display_name.starts_with("dCS Vivaldi") && zone.state == State::Playing)
-                                    .map(|(id, _)| id.clone())
+                                let current_settings = extension_settings.read().await.clone();
+                                let playing_devices: Vec<ZoneDevice> = zones_snapshot.values()
+                                    .filter(|zone| zone.state == State::Playing)
+                                    .filter_map(|zone| current_settings.device_for_zone(zone))
                                     .collect();
 
+                                // dCS devices are re-read by their pollers, which rebroadcast when the format changes
+                                for host in playing_devices.iter().filter_map(|device| device.dcs_host()) {
+                                    dcs_poller.poke(host).await;
+                                }
+                                let has_upnp_playing_zones = playing_devices.iter().any(|device| device.upnp_location().is_some());
+
                                 for (zone_id, zone) in &zones_snapshot {
                                     match zone.state {
                                         State::Stopped => has_stopped_zones.push(zone_id.clone()),
@@ -502,27 +1291,33 @@ impl RoonClient {
 
                                         let zones_clone = zones.clone();
                                         let zones_raw_json_clone = zones_raw_json.clone();
+                                        let settings_clone = extension_settings.clone();
+                                        let zone_keys_clone = zone_keys.clone();
+                                        let dcs_poller_clone = dcs_poller.clone();
                                         let ws_tx_clone = ws_tx.clone();
                                         tokio::spawn(async move {
-                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone).await;
+                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone, settings_clone, zone_keys_clone, dcs_poller_clone).await;
                                             log::debug!("Broadcasting immediate stop for zone (double-stop detected)");
                                             let _ = ws_tx_clone.send(WsMessage::ZonesChanged { now_playing: zone_data, raw_zones, raw_json });
                                         });
                                     } else {
                                         // No pending stop - start delayed broadcast
-                                        log::debug!("Zone {} stopped - delaying broadcast by {}ms", zone_id, STOP_BROADCAST_DELAY_MS);
+                                        log::debug!("Zone {} stopped - delaying broadcast by {}ms", zone_id, crate::config::get().server.stop_broadcast_delay_ms);
 
                                         let ws_tx_clone = ws_tx.clone();
                                         let zones_clone = zones.clone();
                                         let zones_raw_json_clone = zones_raw_json.clone();
+                                        let settings_clone = extension_settings.clone();
+                                        let zone_keys_clone = zone_keys.clone();
+                                        let dcs_poller_clone = dcs_poller.clone();
                                         let zone_id_clone = zone_id.clone();
                                         let pending_stops_clone2 = pending_stops_clone.clone();
 
                                         let task = tokio::spawn(async move {
-                                            tokio::time::sleep(tokio::time::Duration::from_millis(STOP_BROADCAST_DELAY_MS)).await;
+                                            tokio::time::sleep(tokio::time::Duration::from_millis(crate::config::get().server.stop_broadcast_delay_ms)).await;
 
                                             // Build and broadcast stop
-                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone).await;
+                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone, settings_clone, zone_keys_clone, dcs_poller_clone).await;
                                             log::debug!("Broadcasting delayed stop for zone {}", zone_id_clone);
                                             let _ = ws_tx_clone.send(WsMessage::ZonesChanged { now_playing: zone_data, raw_zones, raw_json });
 
@@ -540,24 +1335,27 @@ impl RoonClient {
 
                                 // Broadcast non-stop zone updates immediately
                                 if !has_non_stop_zones.is_empty() && has_non_loading {
-                                    if !has_dcs_playing_zones.is_empty() {
-                                        // If we have dCS zones in Playing state, give dCS device a moment to update
-                                        log::debug!("Found {} dCS zones in Playing state, waiting {}ms for dCS to update",
-                                                   has_dcs_playing_zones.len(), DCS_UPDATE_DELAY_MS);
+                                    if has_upnp_playing_zones {
+                                        // UPnP renderers are read while building, so give them a moment to update
+                                        log::debug!("Found UPnP zones in Playing state, waiting {}ms for the renderers to update",
+                                                   crate::config::get().upnp.update_delay_ms);
 
                                         let ws_tx_clone = ws_tx.clone();
                                         let zones_clone = zones.clone();
                                         let zones_raw_json_clone = zones_raw_json.clone();
+                                        let settings_clone = extension_settings.clone();
+                                        let zone_keys_clone = zone_keys.clone();
+                                        let dcs_poller_clone = dcs_poller.clone();
 
                                         tokio::spawn(async move {
-                                            // Brief delay to let dCS device process the new stream
-                                            tokio::time::sleep(tokio::time::Duration::from_millis(DCS_UPDATE_DELAY_MS)).await;
+                                            // Brief delay to let the renderer process the new stream
+                                            tokio::time::sleep(tokio::time::Duration::from_millis(crate::config::get().upnp.update_delay_ms)).await;
 
-                                            // Build zone data with dCS format
-                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone).await;
+                                            // Build zone data with the device format
+                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone, settings_clone, zone_keys_clone, dcs_poller_clone).await;
 
                                             // Broadcast zone change with full data
-                                            log::debug!("Broadcasting zone change (after dCS delay) with {} zones of data", zone_data.len());
+                                            log::debug!("Broadcasting zone change (after device delay) with {} zones of data", zone_data.len());
                                             for item in &zone_data {
                                                 log::debug!("  Broadcasting zone {}: state={}, track={:?}, dcs_format={:?}",
                                                            item.zone_name, item.state, item.track, item.dcs_format);
@@ -565,13 +1363,16 @@ impl RoonClient {
                                             let _ = ws_tx_clone.send(WsMessage::ZonesChanged { now_playing: zone_data, raw_zones, raw_json });
                                         });
                                     } else {
-                                        // No dCS zones in Playing state, build and broadcast immediately
+                                        // No UPnP zones in Playing state, build and broadcast immediately
                                         let zones_clone = zones.clone();
                                         let zones_raw_json_clone = zones_raw_json.clone();
+                                        let settings_clone = extension_settings.clone();
+                                        let zone_keys_clone = zone_keys.clone();
+                                        let dcs_poller_clone = dcs_poller.clone();
                                         let ws_tx_clone = ws_tx.clone();
                                         tokio::spawn(async move {
-                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone).await;
-                                            log::debug!("Broadcasting zone change (no dCS delay) with {} zones of data", zone_data.len());
+                                            let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone, settings_clone, zone_keys_clone, dcs_poller_clone).await;
+                                            log::debug!("Broadcasting zone change (no device delay) with {} zones of data", zone_data.len());
                                             for item in &zone_data {
                                                 log::debug!("  Broadcasting zone {}: state={}, track={:?}, dcs_format={:?}",
                                                            item.zone_name, item.state, item.track, item.dcs_format);
@@ -591,14 +1392,20 @@ impl RoonClient {
                                 // Remove zones that are gone
                                 for zone_id in zones_removed {
                                     zone_map.remove(&zone_id);
+                                    queue_tracker.remove_zone(&zone_id).await;
+                                    play_history.on_zone_removed(&zone_id).await;
+                                    zone_keys.remove_zone(&zone_id).await;
                                 }
 
                                 // Broadcast zone change with full data via WebSocket
                                 let zones_clone = zones.clone();
                                 let zones_raw_json_clone = zones_raw_json.clone();
+                                let settings_clone = extension_settings.clone();
+                                let zone_keys_clone = zone_keys.clone();
+                                let dcs_poller_clone = dcs_poller.clone();
                                 let ws_tx_clone = ws_tx.clone();
                                 tokio::spawn(async move {
-                                    let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone).await;
+                                    let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(zones_clone, zones_raw_json_clone, settings_clone, zone_keys_clone, dcs_poller_clone).await;
                                     log::debug!("Broadcasting zone removal with {} zones of data", zone_data.len());
                                     for item in &zone_data {
                                         log::debug!("  Broadcasting zone {}: state={}, track={:?}, dcs_format={:?}",
@@ -607,6 +1414,26 @@ impl RoonClient {
                                     let _ = ws_tx_clone.send(WsMessage::ZonesChanged { now_playing: zone_data, raw_zones, raw_json });
                                 });
                             }
+                            Parsed::Outputs(outputs_changed) => {
+                                log::debug!("Roon API Outputs response:\n{:#?}", outputs_changed);
+                                {
+                                    let mut output_map = outputs.write().await;
+                                    for output in outputs_changed {
+                                        output_map.insert(output.output_id.clone(), output);
+                                    }
+                                }
+                                let _ = ws_tx.send(WsMessage::OutputsChanged { outputs: outputs_snapshot(&outputs).await });
+                            }
+                            Parsed::OutputsRemoved(outputs_removed) => {
+                                log::debug!("Roon API OutputsRemoved response:\n{:#?}", outputs_removed);
+                                {
+                                    let mut output_map = outputs.write().await;
+                                    for output_id in outputs_removed {
+                                        output_map.remove(&output_id);
+                                    }
+                                }
+                                let _ = ws_tx.send(WsMessage::OutputsChanged { outputs: outputs_snapshot(&outputs).await });
+                            }
                             Parsed::ZonesSeek(zones_seek) => {
                                 log::trace!("Roon API ZonesSeek response:\n{:#?}", zones_seek);
                                 log::trace!("Zone seek position updated");
@@ -630,142 +1457,34 @@ impl RoonClient {
                             }
                             Parsed::Jpeg((image_key, data)) => {
                                 log::debug!("Roon API JPEG image response: key={}, size={} bytes", image_key, data.len());
-                                images.write().await.insert(image_key, ImageData {
-                                    content_type: "image/jpeg".to_string(),
-                                    data,
-                                });
+                                images.on_response(raw_request_id(&raw_msg), image_key, ImageFormat::Jpeg, data).await;
                             }
                             Parsed::Png((image_key, data)) => {
                                 log::debug!("Roon API PNG image response: key={}, size={} bytes", image_key, data.len());
-                                images.write().await.insert(image_key, ImageData {
-                                    content_type: "image/png".to_string(),
-                                    data,
-                                });
+                                images.on_response(raw_request_id(&raw_msg), image_key, ImageFormat::Png, data).await;
                             }
                             Parsed::BrowseResult(result, session_key) => {
                                 log::debug!("Roon API BrowseResult response:\n{:#?}", result);
-                                log::info!("Browse result - action: {:?}", result.action);
-                                if let Some(item) = &result.item {
-                                    log::info!("  Item title: {}", item.title);
-                                    if let Some(subtitle) = &item.subtitle {
-                                        log::info!("  Item subtitle: {}", subtitle);
-                                    }
-                                }
-                                if let Some(list) = &result.list {
-                                    log::info!("  List title: {}", list.title);
-                                    if let Some(subtitle) = &list.subtitle {
-                                        log::info!("  List subtitle: {}", subtitle);
-                                    }
-
-                                    // Auto-load the list to see what items are available
-                                    let browse_svc = browse_service.read().await;
-                                    if let Some(browse) = browse_svc.as_ref() {
-                                        log::info!("  Loading list items...");
-                                        let opts = LoadOpts {
-                                            multi_session_key: session_key,
-                                            offset: 0,
-                                            count: Some(list.count.min(20)), // Limit to first 20 items
-                                            set_display_offset: 0,
-                                            ..Default::default()
-                                        };
-                                        browse.load(&opts).await;
-                                    }
+                                if session_key.as_deref() == Some(BROWSE_SESSION_KEY) {
+                                    *browse_result.write().await = Some(result);
+                                    browse_ready.notify_waiters();
                                 }
                             }
                             Parsed::LoadResult(result, session_key) => {
-                                log::debug!("Roon API LoadResult response:\n{:#?}", result);
-                                log::info!("Load result - {} items", result.items.len());
-                                let mut now_playing_item_key = None;
-
-                                for (i, item) in result.items.iter().enumerate() {
-                                    log::info!("  Item {}: {}", i, item.title);
-                                    if let Some(subtitle) = &item.subtitle {
-                                        log::info!("    Subtitle: {}", subtitle);
-                                    }
-
-                                    // Look for "Now Playing" or "Queue" items to browse into
-                                    if (item.title.contains("Now Playing") || item.title.contains("Queue"))
-                                        && item.item_key.is_some() && now_playing_item_key.is_none() {
-                                        now_playing_item_key = item.item_key.clone();
-                                    }
-                                }
-
-                                // If we found a "Now Playing" item, browse into it
-                                if let Some(item_key) = now_playing_item_key {
-                                    let browse_svc = browse_service.read().await;
-                                    if let Some(browse) = browse_svc.as_ref() {
-                                        log::info!("Browsing into Now Playing/Queue...");
-                                        let opts = BrowseOpts {
-                                            multi_session_key: session_key,
-                                            item_key: Some(item_key),
-                                            ..Default::default()
-                                        };
-                                        browse.browse(&opts).await;
-                                    }
+                                log::debug!("Roon API LoadResult response: {} items at offset {}", result.items.len(), result.offset);
+                                if session_key.as_deref() == Some(BROWSE_SESSION_KEY) {
+                                    *load_result.write().await = Some(result);
+                                    load_ready.notify_waiters();
                                 }
                             }
                             Parsed::Queue(queue_items) => {
                                 log::debug!("Roon API Queue response:\n{:#?}", queue_items);
-                                log::info!("Queue snapshot - {} items", queue_items.len());
-
-                                // Store queue for the active subscribed zone
-                                let active_zone = active_queue_zone.read().await;
-                                if let Some(zone_id) = active_zone.as_ref() {
-                                    log::info!("Storing queue for active zone: {}", zone_id);
-                                    queues.write().await.insert(zone_id.clone(), queue_items);
-
-                                    // Notify waiting subscribers that queue data has arrived
-                                    queue_ready.notify_waiters();
-                                } else {
-                                    log::warn!("Received queue data but no active queue zone subscription");
-                                }
+                                queue_tracker.on_snapshot(raw_request_id(&raw_msg), queue_items).await;
                             }
                             Parsed::QueueChanges(queue_changes) => {
                                 log::debug!("Roon API QueueChanges response:\n{:#?}", queue_changes);
-                                log::info!("Queue changes - {} operations", queue_changes.len());
-
-                                // Apply changes to the active zone's queue
-                                let active_zone = active_queue_zone.read().await;
-                                if let Some(zone_id) = active_zone.as_ref() {
-                                    let mut queues_map = queues.write().await;
-                                    if let Some(queue) = queues_map.get_mut(zone_id) {
-                                        for change in queue_changes {
-                                            log::info!("  Operation: {:?} at index {}", change.operation, change.index);
-
-                                            match change.operation {
-                                                roon_api::transport::QueueOperation::Insert => {
-                                                    if let Some(items) = &change.items {
-                                                        let idx = change.index;
-                                                        for (i, item) in items.iter().enumerate() {
-                                                            log::info!("    Inserting: {} (id: {})", item.two_line.line1, item.queue_item_id);
-                                                            // Items from queue changes are already QueueItem
-                                                            queue.insert(idx + i, item.clone());
-                                                        }
-                                                    }
-                                                }
-                                                roon_api::transport::QueueOperation::Remove => {
-                                                    let count = change.count.unwrap_or(1);
-                                                    let idx = change.index;
-                                                    log::info!("    Removing {} items at index {}", count, idx);
-                                                    for _ in 0..count {
-                                                        if idx < queue.len() {
-                                                            queue.remove(idx);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        log::info!("Queue updated for zone {} - now has {} items", zone_id, queue.len());
-
-                                        // Notify frontend via WebSocket that queue has changed
-                                        let _ = ws_tx.send(WsMessage::QueueChanged {
-                                            zone_id: zone_id.clone(),
-                                        });
-                                    } else {
-                                        log::warn!("Received queue changes for zone {} but no queue cached", zone_id);
-                                    }
-                                } else {
-                                    log::warn!("Received queue changes but no active queue zone subscription");
+                                if let Some(zone_id) = queue_tracker.on_changes(raw_request_id(&raw_msg), queue_changes).await {
+                                    let _ = ws_tx.send(WsMessage::QueueChanged { zone_id });
                                 }
                             }
                             Parsed::Error(err) => {
@@ -784,7 +1503,8 @@ impl RoonClient {
                 }
             });
         } else {
-            return Err("Failed to start Roon discovery".into());
+            self.connection.set(ConnectionState::Lost).await;
+            return Err("Failed to connect to Roon Core (direct connection and discovery)".into());
         }
 
         Ok(())
@@ -792,7 +1512,12 @@ impl RoonClient {
 
     /// Check if connected to Roon Core
     pub async fn is_connected(&self) -> bool {
-        *self.connected.read().await
+        self.connection.get().await.is_connected()
+    }
+
+    /// Get the connection state (discovering, awaiting authorization, backing off...)
+    pub async fn get_connection_state(&self) -> ConnectionState {
+        self.connection.get().await
     }
 
     /// Get the name of the connected Roon Core
@@ -805,184 +1530,85 @@ impl RoonClient {
         self.zones.read().await.values().cloned().collect()
     }
 
-    /// Build WebSocket zone data with dCS format
+    /// Get all known outputs, sorted by display name
+    pub async fn get_outputs(&self) -> Vec<OutputInfo> {
+        outputs_snapshot(&self.outputs).await
+    }
+
+    /// Build WebSocket zone data with device formats
     /// This method calls the standalone function with the zones Arc
     /// Returns the simplified WsZoneData, raw Zones from Roon, and raw JSON string
     pub async fn build_ws_zone_data(&self) -> (Vec<WsZoneData>, Vec<Zone>, Option<String>) {
-        build_ws_zone_data_from_zones(self.zones.clone(), self.zones_raw_json.clone()).await
-    }
-
-    /// OLD IMPLEMENTATION - kept for reference but not used
-    #[allow(dead_code)]
-    async fn build_ws_zone_data_old(&self) -> Vec<WsZoneData> {
-        use crate::dcs;
-
-        let zones = self.get_zones().await;
-
-        // Process all zones in parallel
-        let zone_futures: Vec<_> = zones.into_iter().map(|zone| {
-            async move {
-                // Fetch dCS format on-demand if this is a dCS Vivaldi zone in Playing state
-                let dcs_format = if zone.display_name.starts_with("dCS Vivaldi")
-                    && format!("{:?}", zone.state).to_lowercase() == "playing" {
-
-                    match dcs::get_playback_info("dcs-vivaldi.local").await {
-                        Ok(playback_info) => {
-                            // Extract format from audio_format field
-                            if let Some(audio_format) = playback_info.audio_format {
-                                // Only return format if bits_per_sample is valid (non-zero)
-                                if let Some(bits) = audio_format.bits_per_sample {
-                                    if bits > 0 {
-                                        let sample_rate_str = if let Some(freq) = audio_format.sample_frequency {
-                                            if freq >= 1000 {
-                                                format!("{} kHz", freq / 1000)
-                                            } else {
-                                                format!("{} Hz", freq)
-                                            }
-                                        } else {
-                                            String::new()
-                                        };
+        build_ws_zone_data_from_zones(self.zones.clone(), self.zones_raw_json.clone(), self.settings.clone(), self.zone_keys.clone(), self.dcs_poller.clone()).await
+    }
 
-                                        let bit_depth_str = format!("{} bit", bits);
+    /// Resolve a zone id, stable zone key, display name, alias or unique name prefix to a zone id
+    /// The error lists the candidates when the input is ambiguous or unknown
+    pub async fn resolve_zone(&self, zone: &str) -> Result<String, String> {
+        let keys = self.zone_keys.snapshot().await;
+        resolve_zone_in(&*self.zones.read().await, &keys, zone)
+    }
 
-                                        if !sample_rate_str.is_empty() {
-                                            Some(format!("{} {}", sample_rate_str, bit_depth_str))
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        log::debug!("dCS format has bits_per_sample=0, not displaying");
-                                        None
-                                    }
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to get dCS playback info: {}", e);
-                            None
-                        }
-                    }
-                } else {
-                    None
-                };
+    /// Zone filter for the play history, which logs zone ids and names: a current zone (by id, key,
+    /// name, alias or prefix) gives its display name; anything else is kept as given, for zones no
+    /// longer in Roon, along with the resolver error to report if nothing was logged under it
+    pub async fn history_zone_filter(&self, zone: &str) -> (String, Option<String>) {
+        match self.resolve_zone(zone).await {
+            Ok(zone_id) => {
+                let name = self.zones.read().await.get(&zone_id).map(|z| z.display_name.clone());
+                (name.unwrap_or(zone_id), None)
+            }
+            Err(e) => (zone.trim().to_string(), Some(e)),
+        }
+    }
 
-                // Extract track info if available
-                let (track, artist, album, position_seconds, length_seconds, image_key, artist_image_keys) =
-                    if let Some(now_playing) = zone.now_playing.as_ref() {
-                        let three_line = &now_playing.three_line;
-                        (
-                            Some(three_line.line1.clone()),
-                            if !three_line.line2.is_empty() {
-                                Some(three_line.line2.clone())
-                            } else {
-                                None
-                            },
-                            if !three_line.line3.is_empty() {
-                                Some(three_line.line3.clone())
-                            } else {
-                                None
-                            },
-                            now_playing.seek_position,
-                            now_playing.length,
-                            now_playing.image_key.clone(),
-                            now_playing.artist_image_keys.clone(),
-                        )
-                    } else {
-                        (None, None, None, None, None, None, None)
-                    };
+    /// Resolve a zone like resolve_zone, also accepting an output id (returned as is)
+    pub async fn resolve_zone_or_output(&self, zone_or_output_id: &str) -> Result<String, String> {
+        {
+            let zones = self.zones.read().await;
+            let is_output = zones.values()
+                .any(|zone| zone.outputs.iter().any(|output| output.output_id == zone_or_output_id));
+            if is_output && !zones.contains_key(zone_or_output_id) {
+                return Ok(zone_or_output_id.to_string());
+            }
+        }
+        self.resolve_zone(zone_or_output_id).await
+    }
 
-                // Extract is_muted from the first output with volume info
-                let is_muted = zone.outputs.iter()
-                    .find_map(|output| output.volume.as_ref().and_then(|v| v.is_muted));
-
-                // Build zone name with selected output display name
-                let zone_name = if let Some(output) = zone.outputs.first() {
-                    // Check if there's a selected source control
-                    if let Some(source_controls) = &output.source_controls {
-                        if let Some(selected_source) = source_controls.iter()
-                            .find(|sc| sc.status == roon_api::transport::Status::Selected) {
-                            format!("{} ({})", zone.display_name, selected_source.display_name)
-                        } else {
-                            zone.display_name.clone()
-                        }
-                    } else {
-                        zone.display_name.clone()
-                    }
-                } else {
-                    zone.display_name.clone()
-                };
+    /// Device mapped to a zone (by id, key, name, alias or prefix), None if it has none
+    pub async fn get_zone_device(&self, zone: &str) -> Result<Option<ZoneDevice>, String> {
+        let zone_id = self.resolve_zone(zone).await?;
+        let settings = self.settings.read().await;
+        Ok(self.zones.read().await.get(&zone_id).and_then(|zone| settings.device_for_zone(zone)))
+    }
 
-                WsZoneData {
-                    zone_id: zone.zone_id.clone(),
-                    zone_name,
-                    state: format!("{:?}", zone.state),
-                    track,
-                    artist,
-                    album,
-                    position_seconds,
-                    length_seconds,
-                    image_key,
-                    artist_image_keys,
-                    is_muted,
-                    dcs_format,
-                    queue_items_remaining: zone.queue_items_remaining,
-                    queue_time_remaining: zone.queue_time_remaining,
-                }
-            }
-        }).collect();
+    /// Have the poller re-read a dCS device soon, e.g. after changing one of its settings (no-op if it isn't polled)
+    pub async fn refresh_dcs_device(&self, host: &str) {
+        self.dcs_poller.poke(host).await;
+    }
 
-        futures_util::future::join_all(zone_futures).await
+    /// Stable zone keys of the current zones (zone_id -> key)
+    pub async fn get_zone_keys(&self) -> HashMap<String, String> {
+        self.zone_keys.snapshot().await
     }
 
     /// Get queue for a specific zone
     pub async fn get_queue(&self, zone_id: &str) -> Option<Vec<QueueItem>> {
-        self.queues.read().await.get(zone_id).cloned()
+        self.queue_tracker.queues.read().await.get(zone_id).cloned()
     }
 
-    /// Subscribe to queue updates for a specific zone
-    /// Unsubscribes from any previously active queue subscription first
-    /// Waits for the queue data to arrive with a 2 second timeout
+    /// Make sure the queue for a zone is subscribed
+    /// Every zone is subscribed as it appears, so this normally returns immediately;
+    /// otherwise it subscribes and waits for the queue data with a 2 second timeout
     pub async fn subscribe_to_queue(&self, zone_id: &str) {
-        let transport_svc = self.transport_service.read().await;
-        if let Some(transport) = transport_svc.as_ref() {
-            // Check if we're already subscribed to this zone
-            let current_zone = self.active_queue_zone.read().await;
-            if current_zone.as_deref() == Some(zone_id) {
-                log::debug!("Already subscribed to queue for zone {}", zone_id);
-                return;
-            }
-            drop(current_zone);
-
-            // Unsubscribe from previous queue if any
-            let mut active_zone = self.active_queue_zone.write().await;
-            if active_zone.is_some() {
-                log::info!("Unsubscribing from previous queue...");
-                transport.unsubscribe_queue().await;
-            }
-
-            // Subscribe to new queue
-            log::info!("Subscribing to queue for zone {}...", zone_id);
-            transport.subscribe_queue(zone_id, 50).await;
-
-            // Update active zone tracking
-            *active_zone = Some(zone_id.to_string());
-            drop(active_zone);
+        if self.queue_tracker.is_subscribed(zone_id).await {
+            log::debug!("Already subscribed to queue for zone {}", zone_id);
+            return;
+        }
 
-            // Wait for queue data to arrive (with 2 second timeout)
-            log::debug!("Waiting for queue data to arrive...");
-            let timeout = tokio::time::Duration::from_secs(2);
-            match tokio::time::timeout(timeout, self.queue_ready.notified()).await {
-                Ok(_) => {
-                    log::debug!("Queue data received for zone {}", zone_id);
-                }
-                Err(_) => {
-                    log::warn!("Timeout waiting for queue data for zone {}", zone_id);
-                }
-            }
+        let transport = self.transport_service.read().await.clone();
+        if let Some(transport) = transport {
+            self.queue_tracker.subscribe(&transport, zone_id).await;
         } else {
             log::warn!("Transport service not available for queue subscription");
         }
@@ -990,14 +1616,14 @@ impl RoonClient {
 
     /// Request an image from Roon
     /// Returns the request ID if successful, None if the service is unavailable
-    pub async fn request_image(&self, image_key: &str, width: u32, height: u32) -> Option<usize> {
-        log::debug!("request_image called for key: {}", image_key);
+    pub async fn request_image(&self, image_key: &str, spec: ImageSpec) -> Option<usize> {
+        log::debug!("request_image called for key: {} ({:?})", image_key, spec);
         let image_service = self.image_service.read().await;
         if let Some(image) = image_service.as_ref() {
             log::debug!("Image service available, sending request");
-            let scaling = Scaling::new(Scale::Fit, width, height);
-            let args = ImageArgs::new(Some(scaling), Some(Format::Jpeg));
-            let result = image.get_image(image_key, args).await;
+            let scaling = Scaling::new(spec.scale.to_roon(), spec.width, spec.height);
+            let args = ImageArgs::new(Some(scaling), Some(spec.format.to_roon()));
+            let result = self.images.request(image_key, spec, image.get_image(image_key, args)).await;
             log::debug!("Image request result: {:?}", result);
             result
         } else {
@@ -1006,9 +1632,121 @@ impl RoonClient {
         }
     }
 
-    /// Get a cached image by key
-    pub async fn get_image(&self, image_key: &str) -> Option<ImageData> {
-        self.images.read().await.get(image_key).cloned()
+    /// Artist, artist image keys and the index currently shown by the rotation for a zone
+    pub async fn get_artist_images(&self, zone_id: &str) -> Option<(Option<String>, Vec<String>, usize)> {
+        let zone = self.zones.read().await.get(zone_id).cloned()?;
+        let now_playing = zone.now_playing.as_ref();
+        let artist = now_playing
+            .map(|np| np.three_line.line2.clone())
+            .filter(|artist| !artist.is_empty());
+        let image_keys = now_playing.and_then(|np| np.artist_image_keys.clone()).unwrap_or_default();
+
+        let index = match self.artist_rotation.read().await.get(zone_id) {
+            Some(rotation) if rotation.image_keys == image_keys => rotation.index,
+            _ => 0,
+        };
+        Some((artist, image_keys, index))
+    }
+
+    /// Rotate through the artist images of every playing zone, broadcasting ArtistImageChanged
+    /// every artist_image_interval_secs (0 pauses the rotation)
+    pub fn start_artist_image_rotation(&self) -> tokio::task::JoinHandle<()> {
+        let zones = self.zones.clone();
+        let settings = self.settings.clone();
+        let artist_rotation = self.artist_rotation.clone();
+        let ws_tx = self.ws_tx.clone();
+
+        tokio::spawn(async move {
+            use tokio::time::{sleep, Duration};
+
+            loop {
+                // Re-read the interval each time so changes in Roon Settings apply
+                let interval_secs = settings.read().await.artist_image_interval_secs;
+                if interval_secs == 0 {
+                    sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+                sleep(Duration::from_secs(interval_secs as u64)).await;
+
+                let zones_snapshot = zones.read().await.clone();
+                let mut rotation = artist_rotation.write().await;
+                rotation.retain(|zone_id, _| zones_snapshot.contains_key(zone_id));
+
+                for (zone_id, zone) in &zones_snapshot {
+                    let image_keys = zone.now_playing.as_ref()
+                        .and_then(|np| np.artist_image_keys.clone())
+                        .unwrap_or_default();
+                    if zone.state != State::Playing || image_keys.len() < 2 {
+                        continue;
+                    }
+
+                    // A new artist starts from the first image
+                    let entry = rotation.entry(zone_id.clone()).or_insert(ArtistRotation { image_keys: Vec::new(), index: 0 });
+                    if entry.image_keys != image_keys {
+                        *entry = ArtistRotation { image_keys, index: 0 };
+                    } else {
+                        entry.index = (entry.index + 1) % entry.image_keys.len();
+                    }
+
+                    let _ = ws_tx.send(WsMessage::ArtistImageChanged {
+                        zone_id: zone_id.clone(),
+                        image_key: entry.image_keys[entry.index].clone(),
+                        index: entry.index,
+                        count: entry.image_keys.len(),
+                    });
+                }
+            }
+        })
+    }
+
+    /// Rebroadcast zone data when a polled dCS device reports a new format,
+    /// so displays pick it up without waiting for the next zone change from Roon
+    pub fn start_device_updates(&self) -> tokio::task::JoinHandle<()> {
+        let zones = self.zones.clone();
+        let zones_raw_json = self.zones_raw_json.clone();
+        let settings = self.settings.clone();
+        let zone_keys = self.zone_keys.clone();
+        let dcs_poller = self.dcs_poller.clone();
+        let ws_tx = self.ws_tx.clone();
+        let mut ws_rx = self.ws_tx.subscribe();
+
+        tokio::spawn(async move {
+            let mut formats: HashMap<String, Option<String>> = HashMap::new(); // host -> last format
+            loop {
+                let state = match ws_rx.recv().await {
+                    Ok(WsMessage::DeviceChanged { state }) => state,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if formats.get(&state.host) == Some(&state.format) {
+                    continue;
+                }
+                formats.insert(state.host.clone(), state.format.clone());
+
+                log::debug!("dCS format on {} is now {:?}, rebroadcasting zones", state.host, state.format);
+                let (zone_data, raw_zones, raw_json) = build_ws_zone_data_from_zones(
+                    zones.clone(), zones_raw_json.clone(), settings.clone(), zone_keys.clone(), dcs_poller.clone()).await;
+                let _ = ws_tx.send(WsMessage::ZonesChanged { now_playing: zone_data, raw_zones, raw_json });
+            }
+        })
+    }
+
+    /// Get a cached image by key, size and format
+    pub async fn get_image(&self, image_key: &str, spec: &ImageSpec) -> Option<ImageData> {
+        self.images.get(image_key, spec).await
+    }
+
+    /// Request an image unless a matching request is already on its way
+    /// Returns a receiver for the response (None if the image service is unavailable);
+    /// await it without holding the client lock
+    pub async fn fetch_image(&self, image_key: &str, spec: ImageSpec) -> Option<tokio::sync::oneshot::Receiver<ImageData>> {
+        let rx = self.images.wait_for(image_key, &spec).await;
+        if self.images.is_pending(image_key, &spec).await {
+            log::debug!("Image request already pending: {} ({:?})", image_key, spec);
+            return Some(rx);
+        }
+        self.request_image(image_key, spec).await.map(|_| rx)
     }
 
     /// Wait for authorization from Roon Core
@@ -1105,27 +1843,508 @@ impl RoonClient {
         }
     }
 
-    /// Mute or unmute an output
-    pub async fn mute_output(&self, zone_id: &str, mute: bool) -> Result<(), String> {
-        use roon_api::transport::volume::Mute;
-
-        log::debug!("Roon API mute_output request: zone_id={}, mute={}", zone_id, mute);
-
-        // First, get the zone to find its output ID
+    /// Collect the output IDs addressed by a zone or output ID
+    /// A zone (by ID, name, alias or prefix) expands to every output in the zone, an output ID addresses just that output
+    async fn resolve_output_ids(&self, zone_or_output_id: &str) -> Result<Vec<String>, String> {
+        let keys = self.zone_keys.snapshot().await;
         let zones = self.zones.read().await;
-        let zone = zones.get(zone_id).ok_or("Zone not found")?;
 
+        let is_output = zones.values()
+            .any(|zone| zone.outputs.iter().any(|output| output.output_id == zone_or_output_id));
+        if is_output && !zones.contains_key(zone_or_output_id) {
+            return Ok(vec![zone_or_output_id.to_string()]);
+        }
+
+        let zone_id = resolve_zone_in(&zones, &keys, zone_or_output_id)?;
+        let zone = &zones[&zone_id];
         if zone.outputs.is_empty() {
             return Err("Zone has no outputs".to_string());
         }
+        Ok(zone.outputs.iter().map(|output| output.output_id.clone()).collect())
+    }
+
+    /// Mute or unmute every output in a zone (or a single output)
+    pub async fn mute_output(&self, zone_or_output_id: &str, mute: bool) -> Result<(), String> {
+        use roon_api::transport::volume::Mute;
+
+        log::debug!("Roon API mute_output request: zone_or_output_id={}, mute={}", zone_or_output_id, mute);
 
-        let output_id = &zone.outputs[0].output_id;
+        let output_ids = self.resolve_output_ids(zone_or_output_id).await?;
 
         let transport = self.transport_service.read().await;
         if let Some(transport) = transport.as_ref() {
             let mute_enum = if mute { Mute::Mute } else { Mute::Unmute };
-            transport.mute(output_id, &mute_enum).await;
-            log::debug!("Roon API mute_output completed successfully");
+            for output_id in &output_ids {
+                transport.mute(output_id, &mute_enum).await;
+            }
+            log::debug!("Roon API mute_output completed successfully for {} output(s)", output_ids.len());
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Change the volume of every output in a zone (or a single output)
+    /// `how` is "absolute", "relative" or "relative_step"; outputs with fixed volume are skipped
+    pub async fn change_volume(&self, zone_or_output_id: &str, how: &str, value: i32) -> Result<(), String> {
+        use roon_api::transport::volume::ChangeMode;
+
+        log::debug!("Roon API change_volume request: zone_or_output_id={}, how={}, value={}", zone_or_output_id, how, value);
+
+        let mode = match how {
+            "absolute" => ChangeMode::Absolute,
+            "relative" => ChangeMode::Relative,
+            "relative_step" | "step" => ChangeMode::RelativeStep,
+            _ => return Err(format!("Invalid volume mode: {} (use absolute, relative or relative_step)", how)),
+        };
+
+        let output_ids = self.resolve_output_ids(zone_or_output_id).await?;
+
+        // Only outputs that report volume info can be changed; the rest are fixed volume
+        let controllable: Vec<String> = {
+            let zones = self.zones.read().await;
+            output_ids.into_iter()
+                .filter(|output_id| {
+                    zones.values()
+                        .flat_map(|zone| zone.outputs.iter())
+                        .any(|output| &output.output_id == output_id && output.volume.is_some())
+                })
+                .collect()
+        };
+
+        if controllable.is_empty() {
+            return Err("No outputs with volume control (fixed volume)".to_string());
+        }
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            for output_id in &controllable {
+                transport.change_volume(output_id, &mode, value).await;
+            }
+            log::debug!("Roon API change_volume completed successfully for {} output(s)", controllable.len());
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Change shuffle, loop and Roon Radio settings for a zone
+    /// Settings passed as None keep their current value; `loop_mode` is "off", "all" or "one"
+    pub async fn change_zone_settings(&self, zone_id: &str, shuffle: Option<bool>, loop_mode: Option<&str>, auto_radio: Option<bool>) -> Result<(), String> {
+        log::debug!("Roon API change_zone_settings request: zone_id={}, shuffle={:?}, loop={:?}, auto_radio={:?}",
+                    zone_id, shuffle, loop_mode, auto_radio);
+
+        // Start from the zone's current settings so unspecified values are preserved
+        let current = {
+            let zones = self.zones.read().await;
+            let zone = zones.get(zone_id).ok_or("Zone not found")?;
+            zone.settings.clone()
+        };
+
+        let repeat = match loop_mode {
+            Some("off") | Some("disabled") => Repeat::Disabled,
+            Some("all") | Some("loop") => Repeat::Loop,
+            Some("one") | Some("loop_one") => Repeat::LoopOne,
+            Some(other) => return Err(format!("Invalid loop mode: {} (use off, all or one)", other)),
+            None => current.repeat,
+        };
+
+        let settings = ZoneSettings {
+            repeat,
+            shuffle: shuffle.unwrap_or(current.shuffle),
+            auto_radio: auto_radio.unwrap_or(current.auto_radio),
+        };
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            transport.change_settings(zone_id, &settings).await;
+            log::debug!("Roon API change_zone_settings completed successfully");
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Group outputs into a single zone
+    /// Each ID may be an output ID or a zone ID (which contributes all of its outputs)
+    pub async fn group_outputs(&self, zone_or_output_ids: &[String]) -> Result<(), String> {
+        log::debug!("Roon API group_outputs request: ids={:?}", zone_or_output_ids);
+
+        let mut output_ids: Vec<String> = Vec::new();
+        for id in zone_or_output_ids {
+            for output_id in self.resolve_output_ids(id).await? {
+                if !output_ids.contains(&output_id) {
+                    output_ids.push(output_id);
+                }
+            }
+        }
+
+        if output_ids.len() < 2 {
+            return Err("At least two outputs are needed to form a group".to_string());
+        }
+
+        // Roon only groups outputs that are compatible with the first one
+        {
+            let zones = self.zones.read().await;
+            let first = zones.values()
+                .flat_map(|zone| zone.outputs.iter())
+                .find(|output| output.output_id == output_ids[0])
+                .ok_or("Output not found")?;
+            let incompatible: Vec<&String> = output_ids[1..].iter()
+                .filter(|id| !first.can_group_with_output_ids.contains(id))
+                .collect();
+            if !incompatible.is_empty() {
+                return Err(format!("Outputs cannot be grouped with {}: {:?}", first.display_name, incompatible));
+            }
+        }
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            transport.group_outputs(output_ids.iter().map(|id| id.as_str()).collect()).await;
+            log::debug!("Roon API group_outputs completed successfully for {} outputs", output_ids.len());
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Split a grouped zone back into its individual outputs
+    /// Accepts a zone ID (ungroups every output) or a single output ID
+    pub async fn ungroup_outputs(&self, zone_or_output_id: &str) -> Result<(), String> {
+        log::debug!("Roon API ungroup_outputs request: zone_or_output_id={}", zone_or_output_id);
+
+        let output_ids = self.resolve_output_ids(zone_or_output_id).await?;
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            transport.ungroup_outputs(output_ids.iter().map(|id| id.as_str()).collect()).await;
+            log::debug!("Roon API ungroup_outputs completed successfully for {} outputs", output_ids.len());
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Move playback (queue and now playing) from one zone to another
+    pub async fn transfer_zone(&self, from_zone_id: &str, to_zone_id: &str) -> Result<(), String> {
+        log::debug!("Roon API transfer_zone request: from={}, to={}", from_zone_id, to_zone_id);
+
+        if from_zone_id == to_zone_id {
+            return Err("Source and destination zones are the same".to_string());
+        }
+
+        {
+            let zones = self.zones.read().await;
+            if !zones.contains_key(from_zone_id) {
+                return Err(format!("Zone not found: {}", from_zone_id));
+            }
+            if !zones.contains_key(to_zone_id) {
+                return Err(format!("Zone not found: {}", to_zone_id));
+            }
+        }
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            transport.transfer_zone(from_zone_id, to_zone_id).await;
+            log::debug!("Roon API transfer_zone completed successfully");
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Send a browse request on our session and wait for the response
+    async fn send_browse(&self, opts: BrowseOpts) -> Result<BrowseResult, String> {
+        let browse_svc = self.browse_service.read().await;
+        let browse = browse_svc.as_ref().ok_or("Browse service not available")?;
+
+        // Register for the notification before sending so a fast response isn't missed
+        let notified = self.browse_ready.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        *self.browse_result.write().await = None;
+
+        browse.browse(&opts).await;
+
+        let timeout = tokio::time::Duration::from_secs(BROWSE_TIMEOUT_SECS);
+        if tokio::time::timeout(timeout, notified).await.is_err() {
+            return Err("Timeout waiting for browse response".to_string());
+        }
+
+        let result = self.browse_result.write().await.take()
+            .ok_or("No browse response received")?;
+        if result.is_error.unwrap_or(false) {
+            return Err(result.message.clone().unwrap_or("Browse request failed".to_string()));
+        }
+        Ok(result)
+    }
+
+    /// Browse into an item (or the root when item_key is None) on our session
+    async fn browse_item(&self, item_key: Option<&str>, input: Option<&str>, pop_all: bool) -> Result<BrowseResult, String> {
+        self.browse_item_in_zone(item_key, input, pop_all, None).await
+    }
+
+    /// Browse into an item with a zone set, so that actions (Play Now, Queue, ...) apply to it
+    async fn browse_item_in_zone(&self, item_key: Option<&str>, input: Option<&str>, pop_all: bool, zone_id: Option<&str>) -> Result<BrowseResult, String> {
+        self.send_browse(BrowseOpts {
+            multi_session_key: Some(BROWSE_SESSION_KEY.to_string()),
+            item_key: item_key.map(|k| k.to_string()),
+            input: input.map(|i| i.to_string()),
+            zone_or_output_id: zone_id.map(|z| z.to_string()),
+            pop_all,
+            ..Default::default()
+        }).await
+    }
+
+    /// Find an item in the current list by title (case-insensitive), loading as needed
+    async fn find_item(&self, title: &str) -> Result<BrowseItem, String> {
+        let mut offset = 0;
+        loop {
+            let page = self.load(offset, BROWSE_PAGE_SIZE).await?;
+            if let Some(item) = page.items.iter().find(|i| i.title.eq_ignore_ascii_case(title)) {
+                return Ok(item.clone());
+            }
+            offset += page.items.len();
+            if page.items.is_empty() || offset >= page.count {
+                return Err(format!("'{}' not found in {}", title, page.title));
+            }
+        }
+    }
+
+    /// Browse the library
+    /// `hierarchy` is a slash-separated path of menu titles from the root (e.g. "Library/Albums"),
+    /// or a shortcut such as "albums", "artists" or "playlists". An empty path is the root.
+    /// When `item_key` is given, browses into that item from the current list instead.
+    /// Returns the first page of the resulting list.
+    pub async fn browse(&self, hierarchy: Option<&str>, item_key: Option<&str>) -> Result<BrowseList, String> {
+        log::debug!("Roon API browse request: hierarchy={:?}, item_key={:?}", hierarchy, item_key);
+
+        if let Some(item_key) = item_key {
+            self.browse_item(Some(item_key), None, false).await?;
+        } else {
+            self.browse_item(None, None, true).await?;
+            let path = hierarchy_path(hierarchy.unwrap_or("").trim_matches('/'));
+            for title in path.split('/').filter(|t| !t.is_empty()) {
+                let item = self.find_item(title).await?;
+                let key = item.item_key.ok_or(format!("'{}' cannot be browsed", title))?;
+                self.browse_item(Some(&key), None, false).await?;
+            }
+        }
+
+        self.load(0, BROWSE_PAGE_SIZE).await
+    }
+
+    /// Load a page of the current browse list
+    pub async fn load(&self, offset: usize, count: usize) -> Result<BrowseList, String> {
+        let browse_svc = self.browse_service.read().await;
+        let browse = browse_svc.as_ref().ok_or("Browse service not available")?;
+
+        let notified = self.load_ready.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        *self.load_result.write().await = None;
+
+        let opts = LoadOpts {
+            multi_session_key: Some(BROWSE_SESSION_KEY.to_string()),
+            offset,
+            count: Some(count),
+            set_display_offset: offset,
+            ..Default::default()
+        };
+        browse.load(&opts).await;
+
+        let timeout = tokio::time::Duration::from_secs(BROWSE_TIMEOUT_SECS);
+        if tokio::time::timeout(timeout, notified).await.is_err() {
+            return Err("Timeout waiting for load response".to_string());
+        }
+
+        let result = self.load_result.write().await.take()
+            .ok_or("No load response received")?;
+        Ok(BrowseList {
+            title: result.list.title.clone(),
+            subtitle: result.list.subtitle.clone(),
+            count: result.list.count,
+            offset: result.offset,
+            items: result.items.iter().map(BrowseItem::from).collect(),
+        })
+    }
+
+    /// Search the library
+    /// Returns the result categories (Artists, Albums, Tracks, ...) which can be browsed into by item_key
+    pub async fn search(&self, query: &str) -> Result<BrowseList, String> {
+        log::debug!("Roon API search request: query={}", query);
+
+        if query.trim().is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+
+        // Search is an input item inside Library
+        self.browse(Some("Library"), None).await?;
+        let mut offset = 0;
+        let search_key = loop {
+            let page = self.load(offset, BROWSE_PAGE_SIZE).await?;
+            if let Some(item) = page.items.iter().find(|i| i.input_prompt.is_some()) {
+                break item.item_key.clone().ok_or("Search item has no key")?;
+            }
+            offset += page.items.len();
+            if page.items.is_empty() || offset >= page.count {
+                return Err("Search is not available in the Library".to_string());
+            }
+        };
+
+        self.browse_item(Some(&search_key), Some(query), false).await?;
+        self.load(0, BROWSE_PAGE_SIZE).await
+    }
+
+    /// Find a library item by name
+    /// `kind` is a search result category (albums, artists, tracks, playlists, composers)
+    /// or "radio" for stations in My Live Radio. Prefers an exact title match, then a partial one;
+    /// None if neither matches. The returned item_key is valid in the current browse session until the next browse.
+    pub async fn find_library_item(&self, kind: &str, name: &str) -> Result<Option<BrowseItem>, String> {
+        log::debug!("Roon API find_library_item request: kind={}, name={}", kind, name);
+
+        let name_lower = name.to_lowercase();
+        let pick = |items: &[BrowseItem]| -> Option<BrowseItem> {
+            items.iter()
+                .filter(|i| i.item_key.is_some())
+                .find(|i| i.title.to_lowercase() == name_lower)
+                .or_else(|| items.iter()
+                    .filter(|i| i.item_key.is_some())
+                    .find(|i| i.title.to_lowercase().contains(&name_lower)))
+                .cloned()
+        };
+
+        if kind.eq_ignore_ascii_case("radio") {
+            let mut page = self.browse(Some("radio"), None).await?;
+            loop {
+                if let Some(item) = pick(&page.items) {
+                    return Ok(Some(item));
+                }
+                let next = page.offset + page.items.len();
+                if page.items.is_empty() || next >= page.count {
+                    return Ok(None);
+                }
+                page = self.load(next, BROWSE_PAGE_SIZE).await?;
+            }
+        }
+
+        // Search results are grouped into categories (Artists, Albums, Tracks, Playlists, ...)
+        let categories = self.search(name).await?;
+        let Some(category) = categories.items.iter().find(|i| i.title.eq_ignore_ascii_case(kind)) else {
+            return Ok(None);
+        };
+        let category_key = category.item_key.clone().ok_or("Search category has no key")?;
+
+        let results = self.browse(None, Some(&category_key)).await?;
+        Ok(pick(&results.items))
+    }
+
+    /// Play a browse item in a zone
+    /// `item_key` comes from the current browse session (browse, search or find_library_item).
+    /// `action` is one of: now, queue, next, radio
+    pub async fn play_item(&self, zone_id: &str, item_key: &str, action: &str) -> Result<(), String> {
+        log::debug!("Roon API play_item request: zone_id={}, item_key={}, action={}", zone_id, item_key, action);
+
+        let titles = play_action_titles(action)
+            .ok_or(format!("Invalid action '{}'. Use: now, queue, next, radio", action))?;
+
+        if !self.zones.read().await.contains_key(zone_id) {
+            return Err(format!("Zone not found: {}", zone_id));
+        }
+
+        // Browsing into an action item (e.g. a radio station) performs it immediately
+        let result = self.browse_item_in_zone(Some(item_key), None, false, Some(zone_id)).await?;
+        if result.list.is_none() {
+            log::debug!("Roon API play_item completed by direct action");
+            return Ok(());
+        }
+
+        // Albums and playlists open a list whose first entry ("Play Album", "Play Playlist")
+        // leads to the actions; tracks open the actions directly
+        let mut list = self.load(0, BROWSE_PAGE_SIZE).await?;
+        let has_action = |list: &BrowseList| list.items.iter()
+            .any(|i| titles.iter().any(|t| i.title.eq_ignore_ascii_case(t)));
+        if !has_action(&list) {
+            let action_list = list.items.iter()
+                .find(|i| i.hint.as_deref() == Some("action_list"))
+                .and_then(|i| i.item_key.clone())
+                .ok_or("Item cannot be played")?;
+            self.browse_item_in_zone(Some(&action_list), None, false, Some(zone_id)).await?;
+            list = self.load(0, BROWSE_PAGE_SIZE).await?;
+        }
+
+        let action_key = list.items.iter()
+            .find(|i| titles.iter().any(|t| i.title.eq_ignore_ascii_case(t)))
+            .and_then(|i| i.item_key.clone())
+            .ok_or(format!("Action '{}' is not available for this item", action))?;
+
+        self.browse_item_in_zone(Some(&action_key), None, false, Some(zone_id)).await?;
+        log::debug!("Roon API play_item completed successfully");
+        Ok(())
+    }
+
+    /// Find the source controls of a zone's (or single output's) outputs
+    /// Returns (output_id, control_key) pairs, limited to `control_key` when given,
+    /// otherwise to controls accepted by `filter`
+    async fn resolve_source_controls<F>(&self, zone_or_output_id: &str, control_key: Option<&str>, filter: F) -> Result<Vec<(String, String)>, String>
+    where
+        F: Fn(&roon_api::transport::SourceControls) -> bool,
+    {
+        let output_ids = self.resolve_output_ids(zone_or_output_id).await?;
+        let zones = self.zones.read().await;
+
+        let controls: Vec<(String, String)> = zones.values()
+            .flat_map(|zone| zone.outputs.iter())
+            .filter(|output| output_ids.contains(&output.output_id))
+            .flat_map(|output| output.source_controls.iter().flatten()
+                .filter(|sc| match control_key {
+                    Some(key) => sc.control_key == key,
+                    None => filter(sc),
+                })
+                .map(|sc| (output.output_id.clone(), sc.control_key.clone())))
+            .collect();
+
+        if controls.is_empty() {
+            return Err(match control_key {
+                Some(key) => format!("Source control not found: {}", key),
+                None => "No matching source controls for this zone or output".to_string(),
+            });
+        }
+        Ok(controls)
+    }
+
+    /// Put the source controls of a zone (or single output) into standby
+    /// When control_key is None, every control that supports standby is used
+    pub async fn standby(&self, zone_or_output_id: &str, control_key: Option<&str>) -> Result<(), String> {
+        log::debug!("Roon API standby request: zone_or_output_id={}, control_key={:?}", zone_or_output_id, control_key);
+
+        let controls = self.resolve_source_controls(zone_or_output_id, control_key, |sc| sc.supports_standby).await?;
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            for (output_id, control_key) in &controls {
+                transport.standby(output_id, Some(control_key)).await;
+            }
+            log::debug!("Roon API standby completed successfully for {} controls", controls.len());
+            Ok(())
+        } else {
+            Err("Transport service not available".to_string())
+        }
+    }
+
+    /// Wake the source controls of a zone (or single output) with the convenience switch
+    /// This selects the input on the device and brings it out of standby
+    pub async fn wake(&self, zone_or_output_id: &str, control_key: Option<&str>) -> Result<(), String> {
+        log::debug!("Roon API convenience_switch request: zone_or_output_id={}, control_key={:?}", zone_or_output_id, control_key);
+
+        let controls = self.resolve_source_controls(zone_or_output_id, control_key, |_| true).await?;
+
+        let transport = self.transport_service.read().await;
+        if let Some(transport) = transport.as_ref() {
+            for (output_id, control_key) in &controls {
+                transport.convenience_switch(output_id, Some(control_key)).await;
+            }
+            log::debug!("Roon API convenience_switch completed successfully for {} controls", controls.len());
             Ok(())
         } else {
             Err("Transport service not available".to_string())
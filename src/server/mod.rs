@@ -2,15 +2,17 @@ use axum::{
     routing::{get, post},
     Router,
     Json,
-    extract::{State, Path, ws::{WebSocket, WebSocketUpgrade}},
+    extract::{State, Path, Query, ws::{WebSocket, WebSocketUpgrade}},
     response::{Html, IntoResponse, Response},
-    http::{StatusCode, header},
+    http::{StatusCode, HeaderMap, header},
 };
 use tower_http::cors::CorsLayer;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use serde::{Deserialize, Serialize};
-use crate::roon::RoonClient;
+use crate::roon::{RoonClient, BROWSE_PAGE_SIZE};
+use crate::image_cache::{ImageData, ImageFormat, ImageScale, ImageSpec};
+use crate::dcs::{DcsClient, DcsError, DcsVolumeSettings};
 use futures_util::StreamExt;
 
 #[derive(Clone)]
@@ -21,16 +23,21 @@ pub struct AppState {
 #[derive(Serialize, Deserialize)]
 pub struct StatusResponse {
     pub connected: bool,
+    pub connection_state: String, // discovering, awaiting_authorization, connected, lost, backing_off
     pub core_name: Option<String>,
+    pub connection_method: Option<String>, // "direct" or "discovery"
+    pub core_address: Option<String>,      // Set for direct connections
     pub message: String,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ZoneInfo {
     pub zone_id: String,
+    pub zone_key: Option<String>, // Stable roon-rd key
     pub display_name: String,
     pub state: String,
     pub devices: Vec<String>,
+    pub outputs: Vec<crate::roon::WsOutputData>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -39,6 +46,27 @@ pub struct ZonesResponse {
     pub count: usize,
 }
 
+#[derive(Serialize)]
+pub struct OutputsResponse {
+    pub outputs: Vec<crate::roon::OutputInfo>,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct ArtistImage {
+    pub image_key: String,
+    pub url: String, // /image URL with the requested size
+}
+
+#[derive(Serialize)]
+pub struct ArtistImagesResponse {
+    pub zone_id: String,
+    pub artist: Option<String>,
+    pub images: Vec<ArtistImage>,
+    pub current_index: usize, // Image shown by the server-side rotation
+    pub interval_secs: u32,   // Rotation interval, 0 when disabled
+}
+
 // NowPlayingInfo removed - now using WsZoneData from roon module
 // This eliminates code duplication between WebSocket and HTTP responses
 
@@ -669,7 +697,9 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
         let nowPlayingZones = [];  // Playing/paused zones from /now-playing
         let timeDisplayMode = {};  // Track time display mode per zone: true = show remaining, false = show total
         let artistImageCarousels = {};  // Track carousel timers per zone
+        let serverArtistRotation = false;  // Set once the server sends artist_image_changed; local carousels stop
         let artistImagesVisible = {};  // Track artist image visibility per zone: true = visible (default), false = hidden
+        let displaySettings = { artist_image_interval_secs: 5 };  // Display options from /settings (Roon Settings > Extensions)
         const placeholderSvg = '<svg viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg"><path d="M12 3v10.55c-.59-.34-1.27-.55-2-.55-2.21 0-4 1.79-4 4s1.79 4 4 4 4-1.79 4-4V7h4V3h-6z"/></svg>';
 
         // SVG icons for different zone types
@@ -715,9 +745,17 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
             default: '<svg viewBox="0 0 24 24" fill="currentColor" style="width: 40px; height: 40px; vertical-align: middle; margin-left: 8px;"><path d="M12 3v9.28c-.47-.17-.97-.28-1.5-.28C8.01 12 6 14.01 6 16.5S8.01 21 10.5 21c2.31 0 4.2-1.75 4.45-4H15V6h4V3h-7z"/></svg>'
         };
 
-        // Detect zone icon based on zone name and device names
-        function getZoneIcon(zoneName, devices) {
-            const nameAndDevices = (zoneName + ' ' + (devices || []).join(' ')).toLowerCase();
+        // Detect zone icon from the zone's mapped device, then its zone and output names
+        function getZoneIcon(zone) {
+            if (zone.device_kind === 'dcs') {
+                return zoneIcons.dac;
+            }
+            if (zone.device_kind === 'upnp') {
+                return zoneIcons.network;
+            }
+
+            const devices = (zone.outputs || []).map(output => output.display_name);
+            const nameAndDevices = (zone.zone_name + ' ' + devices.join(' ')).toLowerCase();
 
             // Check for Oldara Player
             if (nameAndDevices.match(/oldara/)) {
@@ -770,6 +808,9 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
             // Stop existing carousel for this zone if any
             stopArtistImageCarousel(zoneId);
 
+            // The server drives the slideshow when it rotates images itself
+            if (serverArtistRotation) return;
+
             const container = document.querySelector(`.artist-images[data-zone-id="${zoneId}"]`);
             if (!container) return;
 
@@ -789,13 +830,33 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
                 images[currentIndex].classList.add('active');
             };
 
-            // Start the carousel with 5 second interval
-            const intervalId = setInterval(rotateImage, 5000);
+            // Start the carousel with the configured interval (0 disables rotation)
+            const intervalSecs = displaySettings.artist_image_interval_secs;
+            if (!intervalSecs) return;
+            const intervalId = setInterval(rotateImage, intervalSecs * 1000);
 
             // Store the interval ID so we can stop it later
             artistImageCarousels[zoneId] = intervalId;
         }
 
+        function showArtistImage(zoneId, imageKey, index) {
+            // Switch to server-driven rotation
+            if (!serverArtistRotation) {
+                serverArtistRotation = true;
+                Object.keys(artistImageCarousels).forEach(stopArtistImageCarousel);
+            }
+
+            const container = document.querySelector(`.artist-images[data-zone-id="${zoneId}"]`);
+            if (!container || container.classList.contains('hidden')) return;
+
+            const images = container.querySelectorAll('.artist-image');
+            const next = images[index];
+            // Ignore updates for an artist we haven't rendered yet
+            if (!next || !next.getAttribute('src').includes(encodeURIComponent(imageKey))) return;
+            images.forEach(img => img.classList.remove('active'));
+            next.classList.add('active');
+        }
+
         function stopArtistImageCarousel(zoneId) {
             if (artistImageCarousels[zoneId]) {
                 clearInterval(artistImageCarousels[zoneId]);
@@ -951,7 +1012,7 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
                     ` : `
                         <div class="stopped-state">
                             <div class="stopped-zone-info">
-                                <span>${formatZoneName(zone.zone_name)}</span>
+                                <span>${formatZoneName(zone.zone_name)}</span>${getZoneIcon(zone)}
                             </div>
                             <div class="stopped-status">Stopped</div>
                         </div>
@@ -1325,11 +1386,19 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
                     statusEl.textContent = data.core_name ? `Connected to Roon Server: ${data.core_name}` : 'Connected';
                     reconnectBtn.style.display = 'none';
                     updateAuthOverlay(true);  // Hide overlay - connected
-                } else {
+                } else if (data.connection_state === 'awaiting_authorization' || data.connection_state === 'disconnected') {
                     statusEl.className = 'status disconnected';
-                    statusEl.textContent = 'Roon Core Not Connected';
+                    statusEl.textContent = 'Waiting for Authorization in Roon';
                     reconnectBtn.style.display = 'inline-block';
                     updateAuthOverlay(false);  // Show overlay - need authorization
+                } else {
+                    // Discovering, lost, or backing off - the server reconnects on its own
+                    statusEl.className = 'status disconnected';
+                    statusEl.textContent = data.connection_state === 'discovering'
+                        ? 'Looking for Roon Server...'
+                        : 'Roon Server Connection Lost - Reconnecting...';
+                    reconnectBtn.style.display = 'inline-block';
+                    updateAuthOverlay(true);  // Hide overlay - authorization isn't the problem
                 }
             } catch (e) {
                 statusEl.className = 'status disconnected';
@@ -1711,6 +1780,15 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
         let reconnectTimeout = null;
         let wsConnected = false;
 
+        async function updateSettings() {
+            try {
+                const response = await fetch('/settings');
+                displaySettings = await response.json();
+            } catch (e) {
+                console.error('Error fetching settings:', e);
+            }
+        }
+
         function connectWebSocket() {
             const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
             const wsUrl = `${protocol}//${window.location.host}/ws`;
@@ -1724,7 +1802,7 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
                 // Initial data load
                 updateStatus();
                 updateZones();
-                updateNowPlaying();
+                updateSettings().then(updateNowPlaying);
             };
 
             ws.onmessage = (event) => {
@@ -1754,6 +1832,9 @@ const SPA_HTML: &str = r#"<!DOCTYPE html>
                     } else if (msg.type === 'queue_changed') {
                         // Queue has changed - refresh if queue popup is open for this zone
                         refreshQueueIfOpen(msg.zone_id);
+                    } else if (msg.type === 'artist_image_changed') {
+                        // Server-side artist slideshow moved on
+                        showArtistImage(msg.zone_id, msg.image_key, msg.index);
                     }
                 } catch (e) {
                     console.error('Error parsing WebSocket message:', e);
@@ -1804,10 +1885,13 @@ async fn handle_socket(mut socket: WebSocket, state: AppState) {
 
     // Send initial state
     let client = state.roon_client.lock().await;
-    let connected = client.is_connected().await;
+    let connection_state = client.get_connection_state().await;
     drop(client);
 
-    let init_msg = crate::roon::WsMessage::ConnectionChanged { connected };
+    let init_msg = crate::roon::WsMessage::ConnectionChanged {
+        connected: connection_state.is_connected(),
+        state: connection_state,
+    };
     if let Ok(json) = serde_json::to_string(&init_msg) {
         let _ = socket.send(axum::extract::ws::Message::Text(json)).await;
     }
@@ -1850,19 +1934,44 @@ const ROUTES: &[(&str, &str, &str)] = &[
     ("WS", "/ws", "WebSocket connection for real-time updates"),
     ("GET", "/status", "Get Roon connection status (JSON)"),
     ("GET", "/version", "Get server version (JSON)"),
+    ("GET", "/settings", "Get extension settings (JSON)"),
     ("POST", "/reconnect", "Reconnect to Roon Core"),
     ("GET", "/zones", "Get available Roon zones (JSON)"),
+    ("GET", "/outputs", "Get all Roon outputs with volume and source controls (JSON)"),
+    ("GET", "/history", "Get play history, optionally ?zone=<id or name>&since=<date> (JSON)"),
+    ("GET", "/stats", "Get listening statistics, optionally ?zone=&since=&until=&limit= (JSON)"),
     ("GET", "/now-playing", "Get currently playing tracks (JSON)"),
     ("GET", "/queue/:zone_id", "Get queue for a specific zone (JSON)"),
-    ("GET", "/image/:image_key", "Get album art image"),
+    ("GET", "/artist-images/:zone_id", "Get image URLs for the current artist, optionally ?width=&height=&scale=&format= (JSON)"),
+    ("GET", "/image/:image_key", "Get album art image, optionally ?width=&height=&scale=fit|fill|stretch&format=jpeg|png"),
+    ("GET", "/browse", "Browse the library (?hierarchy=, ?item_key=, ?offset=&count=)"),
+    ("GET", "/search", "Search the library (?q=)"),
     ("POST", "/control/:zone_id", "Control playback (play/pause/stop)"),
     ("POST", "/seek/:zone_id", "Seek to position in current track"),
     ("POST", "/mute/:zone_id", "Toggle mute for a zone"),
+    ("POST", "/volume/:zone_id", "Change volume for a zone or output"),
+    ("POST", "/zone-settings/:zone_id", "Change shuffle, loop and Roon Radio settings"),
+    ("POST", "/group", "Group outputs into one zone"),
+    ("POST", "/ungroup/:zone_id", "Split a grouped zone into its outputs"),
+    ("POST", "/transfer/:zone_id", "Move playback to another zone"),
+    ("POST", "/standby/:zone_id", "Put a zone's or output's source controls into standby"),
+    ("POST", "/wake/:zone_id", "Wake a zone's or output's source controls (convenience switch)"),
     ("POST", "/play-from-queue/:zone_id", "Play a specific item from queue"),
+    ("POST", "/play-item/:zone_id", "Play, queue or play next a library item"),
+    ("GET", "/dcs/:host/input", "Get the current and available dCS inputs (host or zone, JSON)"),
+    ("POST", "/dcs/:host/input", "Select a dCS digital input"),
+    ("POST", "/dcs/:host/playmode", "Set the dCS play mode"),
+    ("GET", "/dcs/:host/volume", "Get dCS volume, mute, balance and phase (host or zone, JSON)"),
+    ("POST", "/dcs/:host/volume", "Set dCS volume and/or balance in dB"),
+    ("POST", "/dcs/:host/mute", "Mute, unmute or toggle mute on a dCS device"),
+    ("POST", "/dcs/:host/phase", "Set dCS absolute phase (normal or inverted)"),
 ];
 
 /// Start the web server
 pub async fn start_server(client: Arc<Mutex<RoonClient>>, port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    // Displays follow the server's artist image slideshow via artist_image_changed
+    client.lock().await.start_artist_image_rotation();
+
     let state = AppState {
         roon_client: client,
     };
@@ -1872,15 +1981,36 @@ pub async fn start_server(client: Arc<Mutex<RoonClient>>, port: u16) -> Result<(
         .route("/ws", get(ws_handler))
         .route("/status", get(status_handler))
         .route("/version", get(version_handler))
+        .route("/settings", get(settings_handler))
         .route("/reconnect", post(reconnect_handler))
         .route("/zones", get(zones_handler))
+        .route("/outputs", get(outputs_handler))
+        .route("/history", get(history_handler))
+        .route("/stats", get(stats_handler))
         .route("/now-playing", get(now_playing_handler))
         .route("/queue/:zone_id", get(queue_handler))
+        .route("/artist-images/:zone_id", get(artist_images_handler))
         .route("/image/:image_key", get(image_handler))
+        .route("/browse", get(browse_handler))
+        .route("/search", get(search_handler))
         .route("/control/:zone_id", post(control_handler))
         .route("/seek/:zone_id", post(seek_handler))
         .route("/mute/:zone_id", post(mute_handler))
+        .route("/volume/:zone_id", post(volume_handler))
+        .route("/zone-settings/:zone_id", post(zone_settings_handler))
+        .route("/group", post(group_handler))
+        .route("/ungroup/:zone_id", post(ungroup_handler))
+        .route("/transfer/:zone_id", post(transfer_handler))
+        .route("/standby/:zone_id", post(standby_handler))
+        .route("/wake/:zone_id", post(wake_handler))
         .route("/play-from-queue/:zone_id", post(play_from_queue_handler))
+        .route("/play-item/:zone_id", post(play_item_handler))
+        .route("/dcs/:host/input", get(dcs_input_handler).post(dcs_set_input_handler))
+        .route("/dcs/:host/playmode", post(dcs_set_play_mode_handler))
+        // Experimental: the dCS volume settings paths are unverified (see dcs::VOLUME_PATH)
+        .route("/dcs/:host/volume", get(dcs_volume_handler).post(dcs_set_volume_handler))
+        .route("/dcs/:host/mute", post(dcs_mute_handler))
+        .route("/dcs/:host/phase", post(dcs_phase_handler))
         .layer(CorsLayer::permissive())
         .with_state(state);
 
@@ -1911,22 +2041,34 @@ async fn spa_handler() -> Html<String> {
 async fn status_handler(State(state): State<AppState>) -> Json<StatusResponse> {
     let client = state.roon_client.lock().await;
 
-    let connected = client.is_connected().await;
+    let connection_state = client.get_connection_state().await;
+    let connected = connection_state.is_connected();
     let core_name = client.get_core_name().await;
+    let method = client.get_connection_method();
 
-    let message = if connected {
-        "Connected to Roon Core".to_string()
-    } else {
-        "Not connected. Please authorize the extension in Roon Settings > Extensions".to_string()
+    let message = match &connection_state {
+        crate::roon::ConnectionState::Connected => "Connected to Roon Core".to_string(),
+        crate::roon::ConnectionState::AwaitingAuthorization | crate::roon::ConnectionState::Disconnected => {
+            "Not connected. Please authorize the extension in Roon Settings > Extensions".to_string()
+        }
+        state => format!("Not connected. {}", state),
     };
 
     Json(StatusResponse {
         connected,
+        connection_state: connection_state.name().to_string(),
         core_name,
+        connection_method: method.as_ref().map(|m| m.name().to_string()),
+        core_address: method.and_then(|m| m.core_address()),
         message,
     })
 }
 
+async fn settings_handler(State(state): State<AppState>) -> Json<crate::settings::ExtensionSettings> {
+    let client = state.roon_client.lock().await;
+    Json(client.get_settings().await)
+}
+
 async fn version_handler() -> Json<VersionResponse> {
     Json(VersionResponse {
         version: env!("CARGO_PKG_VERSION").to_string(),
@@ -1956,16 +2098,20 @@ async fn zones_handler(State(state): State<AppState>) -> Json<ZonesResponse> {
     let client = state.roon_client.lock().await;
 
     let zones = client.get_zones().await;
+    let zone_keys = client.get_zone_keys().await;
     let zone_infos: Vec<ZoneInfo> = zones.into_iter().map(|zone| {
         let devices = zone.outputs.iter()
             .map(|output| output.display_name.clone())
             .collect();
+        let outputs = crate::roon::zone_outputs(&zone);
 
         ZoneInfo {
+            zone_key: zone_keys.get(&zone.zone_id).cloned(),
             zone_id: zone.zone_id,
             display_name: zone.display_name,
             state: format!("{:?}", zone.state),
             devices,
+            outputs,
         }
     }).collect();
 
@@ -1977,6 +2123,15 @@ async fn zones_handler(State(state): State<AppState>) -> Json<ZonesResponse> {
     })
 }
 
+async fn outputs_handler(State(state): State<AppState>) -> Json<OutputsResponse> {
+    let client = state.roon_client.lock().await;
+
+    let outputs = client.get_outputs().await;
+    let count = outputs.len();
+
+    Json(OutputsResponse { outputs, count })
+}
+
 #[derive(Serialize)]
 struct QueueItemInfo {
     queue_item_id: u32,
@@ -1998,10 +2153,14 @@ struct QueueResponse {
 async fn queue_handler(
     Path(zone_id): Path<String>,
     State(state): State<AppState>,
-) -> Json<QueueResponse> {
+) -> Response {
     let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
 
-    // Subscribe to this zone's queue (will unsubscribe from previous zone if any)
+    // Queues are kept live for every zone; this only subscribes if the zone is new
     client.subscribe_to_queue(&zone_id).await;
 
     let queue_items = client.get_queue(&zone_id).await.unwrap_or_default();
@@ -2028,7 +2187,7 @@ async fn queue_handler(
         }
     }).collect();
 
-    Json(QueueResponse { items })
+    Json(QueueResponse { items }).into_response()
 }
 
 async fn now_playing_handler(State(state): State<AppState>) -> Json<NowPlayingResponse> {
@@ -2050,44 +2209,76 @@ async fn now_playing_handler(State(state): State<AppState>) -> Json<NowPlayingRe
     })
 }
 
+#[derive(Deserialize)]
+struct ImageQuery {
+    width: Option<u32>,  // Defaults to height, then the album art size setting
+    height: Option<u32>, // Defaults to width
+    #[serde(default)]
+    scale: ImageScale,   // fit (default), fill, stretch
+    format: Option<ImageFormat>, // jpeg (default) or png
+}
+
+/// Whether an If-None-Match header matches the entity tag
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers.get_all(header::IF_NONE_MATCH).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+/// Image response with caching headers
+fn image_response(image_data: ImageData, etag: String) -> Response {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, image_data.content_type),
+         (header::CACHE_CONTROL, format!("public, max-age={}", crate::config::get().server.image_cache_max_age_secs)),
+         (header::ETAG, etag)],
+        image_data.data,
+    ).into_response()
+}
+
 async fn image_handler(
     State(state): State<AppState>,
     Path(image_key): Path<String>,
+    Query(query): Query<ImageQuery>,
+    headers: HeaderMap,
 ) -> Response {
     log::info!("Image request for key: {}", image_key);
     let client = state.roon_client.lock().await;
 
+    let default_size = client.get_settings().await.album_art_size;
+    let width = query.width.or(query.height).unwrap_or(default_size);
+    let height = query.height.or(query.width).unwrap_or(default_size);
+    let spec = ImageSpec::new(width, height, query.scale, query.format.unwrap_or(ImageFormat::Jpeg));
+
+    // Image keys name immutable images, so a matching tag is always still valid
+    let etag = spec.etag(&image_key);
+    if etag_matches(&headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
     // Check if image is already cached
-    if let Some(image_data) = client.get_image(&image_key).await {
+    if let Some(image_data) = client.get_image(&image_key, &spec).await {
         log::info!("Serving cached image: {} ({} bytes)", image_key, image_data.data.len());
-        return (
-            StatusCode::OK,
-            [(header::CONTENT_TYPE, image_data.content_type.clone()),
-             (header::CACHE_CONTROL, "public, max-age=3600".to_string())],
-            image_data.data,
-        ).into_response();
+        return image_response(image_data, etag);
     }
 
-    // Request the image from Roon
-    log::info!("Image not cached, requesting from Roon...");
-    if client.request_image(&image_key, 300, 300).await.is_some() {
-        log::info!("Image request sent, waiting for response...");
-        // Wait a bit for the image to arrive (with timeout)
-        for i in 0..20 {
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            if let Some(image_data) = client.get_image(&image_key).await {
-                log::info!("Image received after {}ms: {} ({} bytes)", i * 100, image_key, image_data.data.len());
-                return (
-                    StatusCode::OK,
-                    [(header::CONTENT_TYPE, image_data.content_type.clone()),
-                     (header::CACHE_CONTROL, "public, max-age=3600".to_string())],
-                    image_data.data,
-                ).into_response();
+    // Request the image from Roon and wait for the response without holding the client
+    log::info!("Image not cached, requesting {:?} from Roon...", spec);
+    let receiver = client.fetch_image(&image_key, spec).await;
+    drop(client);
+
+    let timeout_ms = crate::config::get().server.image_timeout_ms;
+    match receiver {
+        Some(receiver) => match tokio::time::timeout(tokio::time::Duration::from_millis(timeout_ms), receiver).await {
+            Ok(Ok(image_data)) => {
+                log::info!("Image received: {} ({} bytes)", image_key, image_data.data.len());
+                return image_response(image_data, etag);
             }
-        }
-        log::warn!("Image request timed out after 2 seconds");
-    } else {
-        log::warn!("Failed to send image request to Roon");
+            _ => log::warn!("Image request timed out after {}ms", timeout_ms),
+        },
+        None => log::warn!("Failed to send image request to Roon"),
     }
 
     // Image not found or timeout
@@ -2095,6 +2286,18 @@ async fn image_handler(
     StatusCode::NOT_FOUND.into_response()
 }
 
+/// Resolve a zone id, name, alias or prefix from a request, or the 404 to send back
+async fn resolve_zone(client: &RoonClient, zone: &str) -> Result<String, Response> {
+    client.resolve_zone(zone).await
+        .map_err(|e| (StatusCode::NOT_FOUND, e).into_response())
+}
+
+/// Like resolve_zone, for routes that also take an output id
+async fn resolve_zone_or_output(client: &RoonClient, zone_or_output_id: &str) -> Result<String, Response> {
+    client.resolve_zone_or_output(zone_or_output_id).await
+        .map_err(|e| (StatusCode::NOT_FOUND, e).into_response())
+}
+
 #[derive(Deserialize)]
 struct ControlRequest {
     control: String,
@@ -2106,6 +2309,10 @@ async fn control_handler(
     Json(payload): Json<ControlRequest>,
 ) -> Response {
     let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
 
     match client.control_zone(&zone_id, &payload.control).await {
         Ok(_) => (StatusCode::OK, "Control command sent").into_response(),
@@ -2124,6 +2331,10 @@ async fn seek_handler(
     Json(payload): Json<SeekRequest>,
 ) -> Response {
     let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
 
     match client.seek_zone(&zone_id, payload.seconds).await {
         Ok(_) => (StatusCode::OK, "Seek command sent").into_response(),
@@ -2142,6 +2353,10 @@ async fn play_from_queue_handler(
     Json(payload): Json<PlayFromQueueRequest>,
 ) -> Response {
     let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
 
     match client.play_from_queue_item(&zone_id, payload.queue_item_id).await {
         Ok(_) => (StatusCode::OK, "Play from queue command sent").into_response(),
@@ -2160,9 +2375,572 @@ async fn mute_handler(
     Json(payload): Json<MuteRequest>,
 ) -> Response {
     let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone_or_output(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
 
     match client.mute_output(&zone_id, payload.mute).await {
         Ok(_) => (StatusCode::OK, "Mute command sent").into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
     }
 }
+
+#[derive(Deserialize)]
+struct VolumeRequest {
+    how: String,
+    value: i32,
+}
+
+async fn volume_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    Json(payload): Json<VolumeRequest>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone_or_output(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+
+    match client.change_volume(&zone_id, &payload.how, payload.value).await {
+        Ok(_) => (StatusCode::OK, "Volume command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ZoneSettingsRequest {
+    shuffle: Option<bool>,
+    #[serde(rename = "loop")]
+    loop_mode: Option<String>,
+    auto_radio: Option<bool>,
+}
+
+async fn zone_settings_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    Json(payload): Json<ZoneSettingsRequest>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+
+    match client.change_zone_settings(&zone_id, payload.shuffle, payload.loop_mode.as_deref(), payload.auto_radio).await {
+        Ok(_) => (StatusCode::OK, "Zone settings command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct GroupRequest {
+    output_ids: Vec<String>,
+}
+
+async fn group_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<GroupRequest>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    for id in &payload.output_ids {
+        if let Err(response) = resolve_zone_or_output(&client, id).await {
+            return response;
+        }
+    }
+
+    match client.group_outputs(&payload.output_ids).await {
+        Ok(_) => (StatusCode::OK, "Group command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+async fn ungroup_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone_or_output(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+
+    match client.ungroup_outputs(&zone_id).await {
+        Ok(_) => (StatusCode::OK, "Ungroup command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct TransferRequest {
+    to_zone_id: String,
+}
+
+async fn transfer_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    Json(payload): Json<TransferRequest>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+    let to_zone_id = match resolve_zone(&client, &payload.to_zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+
+    match client.transfer_zone(&zone_id, &to_zone_id).await {
+        Ok(_) => (StatusCode::OK, "Transfer command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct BrowseQuery {
+    hierarchy: Option<String>,
+    item_key: Option<String>,
+    offset: Option<usize>,
+    count: Option<usize>,
+}
+
+async fn browse_handler(
+    State(state): State<AppState>,
+    Query(query): Query<BrowseQuery>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+
+    // Paging through the current list: only offset/count given
+    let result = if query.hierarchy.is_none() && query.item_key.is_none() && query.offset.is_some() {
+        client.load(query.offset.unwrap_or(0), query.count.unwrap_or(BROWSE_PAGE_SIZE)).await
+    } else {
+        client.browse(query.hierarchy.as_deref(), query.item_key.as_deref()).await
+    };
+
+    match result {
+        Ok(list) => Json(list).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+}
+
+async fn search_handler(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+
+    match client.search(&query.q).await {
+        Ok(list) => Json(list).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct PlayItemRequest {
+    item_key: Option<String>, // From the last /browse or /search
+    kind: Option<String>,     // Or look up by name: albums, tracks, playlists, radio
+    name: Option<String>,
+    action: Option<String>,   // now (default), queue, next, radio
+}
+
+async fn play_item_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    Json(payload): Json<PlayItemRequest>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+
+    let action = payload.action.unwrap_or_else(|| "now".to_string());
+    if crate::roon::play_action_titles(&action).is_none() {
+        return (StatusCode::BAD_REQUEST, format!("Invalid action '{}'. Use: now, queue, next, radio", action)).into_response();
+    }
+
+    let item_key = match (payload.item_key, payload.kind, payload.name) {
+        (Some(item_key), _, _) => item_key,
+        (None, Some(kind), Some(name)) => {
+            match client.find_library_item(&kind, &name).await {
+                Ok(Some(item)) => match item.item_key {
+                    Some(key) => key,
+                    None => return (StatusCode::NOT_FOUND, format!("'{}' cannot be played", item.title)).into_response(),
+                },
+                Ok(None) => return (StatusCode::NOT_FOUND, format!("No {} found matching '{}'", kind.to_lowercase(), name)).into_response(),
+                Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+            }
+        }
+        _ => return (StatusCode::BAD_REQUEST, "Provide item_key, or kind and name".to_string()).into_response(),
+    };
+
+    match client.play_item(&zone_id, &item_key, &action).await {
+        Ok(_) => (StatusCode::OK, "Play command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct SourceControlRequest {
+    control_key: Option<String>,
+}
+
+async fn standby_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    payload: Option<Json<SourceControlRequest>>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone_or_output(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+    let control_key = payload.and_then(|Json(p)| p.control_key);
+
+    match client.standby(&zone_id, control_key.as_deref()).await {
+        Ok(_) => (StatusCode::OK, "Standby command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+async fn wake_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    payload: Option<Json<SourceControlRequest>>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone_or_output(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+    let control_key = payload.and_then(|Json(p)| p.control_key);
+
+    match client.wake(&zone_id, control_key.as_deref()).await {
+        Ok(_) => (StatusCode::OK, "Wake command sent").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    zone: Option<String>,  // Zone id or display name
+    since: Option<String>, // YYYY-MM-DD or RFC 3339 timestamp
+}
+
+/// Zone filter for /history and /stats (see RoonClient::history_zone_filter)
+async fn history_zone_filter(state: &AppState, zone: Option<String>) -> (Option<String>, Option<String>) {
+    match zone {
+        Some(zone) => {
+            let (zone, error) = state.roon_client.lock().await.history_zone_filter(&zone).await;
+            (Some(zone), error)
+        }
+        None => (None, None),
+    }
+}
+
+async fn history_handler(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    let since = match query.since.as_deref().map(crate::history::parse_since).transpose() {
+        Ok(since) => since,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let (zone, zone_error) = history_zone_filter(&state, query.zone).await;
+
+    match crate::history::read_history_async(zone, since).await {
+        Ok(records) if records.is_empty() && zone_error.is_some() => {
+            (StatusCode::NOT_FOUND, zone_error.unwrap_or_default()).into_response()
+        }
+        Ok(records) => Json(records).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct StatsQuery {
+    zone: Option<String>,  // Zone id or display name
+    since: Option<String>, // YYYY-MM-DD or RFC 3339 timestamp, default a week ago
+    until: Option<String>, // Same formats, a date includes the whole day; default now
+    limit: Option<usize>,  // Entries per top list
+}
+
+async fn stats_handler(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Response {
+    let range = query.since.as_deref().map(crate::history::parse_since).transpose()
+        .and_then(|since| Ok((since, query.until.as_deref().map(crate::history::parse_until).transpose()?)));
+    let (since, until) = match range {
+        Ok(range) => range,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let (zone, zone_error) = history_zone_filter(&state, query.zone).await;
+
+    // Reads the whole play history log
+    let limit = query.limit.unwrap_or(crate::stats::DEFAULT_TOP_COUNT);
+    let stats = tokio::task::spawn_blocking(move || crate::stats::compute(zone.as_deref(), since, until, limit))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|stats| stats);
+    match stats {
+        Ok(stats) if stats.plays == 0 && zone_error.is_some() => {
+            (StatusCode::NOT_FOUND, zone_error.unwrap_or_default()).into_response()
+        }
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+async fn artist_images_handler(
+    State(state): State<AppState>,
+    Path(zone_id): Path<String>,
+    Query(query): Query<ImageQuery>,
+) -> Response {
+    let client = state.roon_client.lock().await;
+    let zone_id = match resolve_zone(&client, &zone_id).await {
+        Ok(zone_id) => zone_id,
+        Err(response) => return response,
+    };
+
+    let Some((artist, image_keys, current_index)) = client.get_artist_images(&zone_id).await else {
+        return (StatusCode::NOT_FOUND, format!("Zone not found: {}", zone_id)).into_response();
+    };
+    let interval_secs = client.get_settings().await.artist_image_interval_secs;
+    drop(client);
+
+    // Pass the size and format through to the /image URLs
+    let mut params = Vec::new();
+    if let Some(width) = query.width {
+        params.push(format!("width={}", width));
+    }
+    if let Some(height) = query.height {
+        params.push(format!("height={}", height));
+    }
+    if query.scale != ImageScale::Fit {
+        params.push(format!("scale={}", query.scale.name()));
+    }
+    if query.format == Some(ImageFormat::Png) {
+        params.push("format=png".to_string());
+    }
+    let suffix = if params.is_empty() { String::new() } else { format!("?{}", params.join("&")) };
+
+    let images = image_keys.into_iter()
+        .map(|image_key| ArtistImage {
+            url: format!("/image/{}{}", urlencoding::encode(&image_key), suffix),
+            image_key,
+        })
+        .collect();
+
+    Json(ArtistImagesResponse {
+        zone_id,
+        artist,
+        images,
+        current_index,
+        interval_secs,
+    }).into_response()
+}
+
+/// dCS host for a path argument: a zone mapped to a dCS device gives its host, otherwise it
+/// must be a configured dCS host, so requests can't be sent to arbitrary hosts
+async fn resolve_dcs_host(state: &AppState, host: &str) -> Result<String, Response> {
+    let client = state.roon_client.lock().await;
+    if let Ok(Some(device)) = client.get_zone_device(host).await {
+        if let Some(dcs_host) = device.dcs_host() {
+            return Ok(dcs_host.to_string());
+        }
+    }
+    client.get_settings().await.dcs_hosts().into_iter()
+        .find(|known| known.eq_ignore_ascii_case(host))
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown dCS device: {} (use a zone or host from the zone devices or dCS host setting)", host)).into_response())
+}
+
+fn dcs_error_response(e: DcsError) -> Response {
+    let status = match e {
+        DcsError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+        DcsError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    (status, e.to_string()).into_response()
+}
+
+async fn dcs_input_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+) -> Response {
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    match DcsClient::new(&host).get_input_info().await {
+        Ok(info) => Json(info).into_response(),
+        Err(e) => dcs_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct DcsInputRequest {
+    input: String,
+}
+
+async fn dcs_set_input_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+    Json(payload): Json<DcsInputRequest>,
+) -> Response {
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    match DcsClient::new(&host).set_input(&payload.input).await {
+        Ok(()) => {
+            state.roon_client.lock().await.refresh_dcs_device(&host).await;
+            (StatusCode::OK, format!("Input set to {}", payload.input)).into_response()
+        }
+        Err(e) => dcs_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct DcsPlayModeRequest {
+    mode: String,
+}
+
+async fn dcs_set_play_mode_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+    Json(payload): Json<DcsPlayModeRequest>,
+) -> Response {
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    match DcsClient::new(&host).set_play_mode(&payload.mode).await {
+        Ok(()) => {
+            state.roon_client.lock().await.refresh_dcs_device(&host).await;
+            (StatusCode::OK, format!("Play mode set to {}", payload.mode)).into_response()
+        }
+        Err(e) => dcs_error_response(e),
+    }
+}
+
+async fn dcs_volume_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+) -> Response {
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    match DcsClient::new(&host).get_volume_settings().await {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e) => dcs_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct DcsVolumeRequest {
+    volume_db: Option<f64>,
+    balance_db: Option<f64>,
+}
+
+async fn dcs_set_volume_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+    Json(payload): Json<DcsVolumeRequest>,
+) -> Response {
+    if payload.volume_db.is_none() && payload.balance_db.is_none() {
+        return (StatusCode::BAD_REQUEST, "Expected volume_db and/or balance_db").into_response();
+    }
+
+    // Check both before changing either
+    let checked = payload.volume_db.map_or(Ok(()), crate::dcs::check_volume)
+        .and(payload.balance_db.map_or(Ok(()), crate::dcs::check_balance));
+    if let Err(e) = checked {
+        return dcs_error_response(e);
+    }
+
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    let dcs = DcsClient::new(&host);
+    if let Some(volume_db) = payload.volume_db {
+        if let Err(e) = dcs.set_volume(volume_db).await {
+            return dcs_error_response(e);
+        }
+    }
+    if let Some(balance_db) = payload.balance_db {
+        if let Err(e) = dcs.set_balance(balance_db).await {
+            return dcs_error_response(e);
+        }
+    }
+
+    state.roon_client.lock().await.refresh_dcs_device(&host).await;
+    (StatusCode::OK, "Volume updated").into_response()
+}
+
+#[derive(Deserialize)]
+struct DcsMuteRequest {
+    mute: Option<bool>, // Toggles when omitted
+}
+
+async fn dcs_mute_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+    Json(payload): Json<DcsMuteRequest>,
+) -> Response {
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    let dcs = DcsClient::new(&host);
+
+    let mute = match payload.mute {
+        Some(mute) => mute,
+        None => match dcs.get_volume_settings().await {
+            Ok(DcsVolumeSettings { mute: Some(mute), .. }) => !mute,
+            Ok(_) => return (StatusCode::BAD_GATEWAY, "Device did not report its mute state").into_response(),
+            Err(e) => return dcs_error_response(e),
+        },
+    };
+
+    match dcs.set_mute(mute).await {
+        Ok(()) => {
+            state.roon_client.lock().await.refresh_dcs_device(&host).await;
+            (StatusCode::OK, if mute { "Muted" } else { "Unmuted" }).into_response()
+        }
+        Err(e) => dcs_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct DcsPhaseRequest {
+    inverted: bool,
+}
+
+async fn dcs_phase_handler(
+    State(state): State<AppState>,
+    Path(host): Path<String>,
+    Json(payload): Json<DcsPhaseRequest>,
+) -> Response {
+    let host = match resolve_dcs_host(&state, &host).await {
+        Ok(host) => host,
+        Err(response) => return response,
+    };
+    match DcsClient::new(&host).set_phase(payload.inverted).await {
+        Ok(()) => {
+            state.roon_client.lock().await.refresh_dcs_device(&host).await;
+            (StatusCode::OK, if payload.inverted { "Phase inverted" } else { "Phase normal" }).into_response()
+        }
+        Err(e) => dcs_error_response(e),
+    }
+}
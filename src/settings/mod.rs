@@ -0,0 +1,267 @@
+use roon_api::settings::{Layout, Widget, Textbox, Integer};
+use roon_api::transport::Zone;
+use serde::{Deserialize, Serialize};
+
+/// Zone name prefix that is treated as a dCS zone when it has no explicit device mapping
+const DCS_ZONE_PREFIX: &str = "dCS Vivaldi";
+
+/// Extension settings, editable from Roon Settings > Extensions
+/// Persisted in the Roon state file under the "settings" key, holding only the fields changed in Roon
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtensionSettings {
+    /// Roon Core to connect to directly ("host" or "host:port"), skipping discovery
+    pub core_address: String,
+    /// dCS device queried for "dCS Vivaldi" zones and used by dcs-* commands without a host
+    pub dcs_host: String,
+    /// Zone-to-device mapping: "Zone or Output Name=device" entries separated by commas
+    pub zone_devices: String,
+    /// Web server port, used when --port is not given (takes effect on restart)
+    pub server_port: u16,
+    /// Seconds between artist images in the web display (0 disables rotation)
+    pub artist_image_interval_secs: u32,
+    /// Size in pixels of album art requested from Roon
+    pub album_art_size: u32,
+}
+
+/// Values from the config file (or its defaults), for every field not changed in Roon
+impl Default for ExtensionSettings {
+    fn default() -> Self {
+        let config = crate::config::get();
+        ExtensionSettings {
+            core_address: config.roon.core.clone().unwrap_or_default(),
+            dcs_host: config.dcs.host.clone(),
+            zone_devices: config.zones.iter()
+                .map(|(zone, host)| format!("{}={}", zone, host))
+                .collect::<Vec<_>>()
+                .join(", "),
+            server_port: config.server.port,
+            artist_image_interval_secs: config.display.artist_image_interval_secs,
+            album_art_size: config.display.album_art_size,
+        }
+    }
+}
+
+/// External device a zone or output plays through, used for format lookup, device commands and icons
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZoneDevice {
+    Dcs(String),  // Host name or IP
+    Upnp(String), // Renderer description URL
+    None,         // Explicitly no device (overrides the "dCS Vivaldi" name default)
+}
+
+impl ZoneDevice {
+    /// Parse a mapping value: "dcs:host", "upnp:http://...", "none", a bare URL (UPnP) or a bare host (dCS)
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("none") {
+            return Some(ZoneDevice::None);
+        }
+        if let Some(host) = value.strip_prefix("dcs:") {
+            let host = host.trim();
+            return if host.is_empty() { None } else { Some(ZoneDevice::Dcs(host.to_string())) };
+        }
+        if let Some(location) = value.strip_prefix("upnp:") {
+            let location = location.trim();
+            return if location.starts_with("http://") || location.starts_with("https://") {
+                Some(ZoneDevice::Upnp(location.to_string()))
+            } else {
+                None
+            };
+        }
+        if value.starts_with("http://") || value.starts_with("https://") {
+            return Some(ZoneDevice::Upnp(value.to_string()));
+        }
+        if value.is_empty() || value.contains(char::is_whitespace) {
+            None
+        } else {
+            Some(ZoneDevice::Dcs(value.to_string()))
+        }
+    }
+
+    /// "dcs", "upnp" or "none"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ZoneDevice::Dcs(_) => "dcs",
+            ZoneDevice::Upnp(_) => "upnp",
+            ZoneDevice::None => "none",
+        }
+    }
+
+    /// dCS host, if this is a dCS device
+    pub fn dcs_host(&self) -> Option<&str> {
+        match self {
+            ZoneDevice::Dcs(host) => Some(host),
+            _ => None,
+        }
+    }
+
+    /// UPnP renderer location, if this is a UPnP device
+    pub fn upnp_location(&self) -> Option<&str> {
+        match self {
+            ZoneDevice::Upnp(location) => Some(location),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ZoneDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZoneDevice::Dcs(host) => write!(f, "dcs:{}", host),
+            ZoneDevice::Upnp(location) => write!(f, "upnp:{}", location),
+            ZoneDevice::None => write!(f, "none"),
+        }
+    }
+}
+
+impl ExtensionSettings {
+    /// Fields that differ from the config file, as saved in the Roon state file
+    /// Fields left alone in Roon are not stored, so later config file edits still apply to them
+    pub fn overrides(&self) -> serde_json::Value {
+        let file = serde_json::to_value(ExtensionSettings::default()).unwrap_or_default();
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(mut fields)) => {
+                fields.retain(|key, value| file.get(key) != Some(value));
+                serde_json::Value::Object(fields)
+            }
+            _ => serde_json::Value::Object(Default::default()),
+        }
+    }
+
+    /// Parse the zone-to-device mapping into (zone or output name, device) pairs
+    pub fn zone_device_map(&self) -> Vec<(String, ZoneDevice)> {
+        self.zone_devices
+            .split([',', ';', '\n'])
+            .filter_map(|entry| {
+                let (zone, device) = entry.split_once('=')?;
+                let zone = zone.trim();
+                if zone.is_empty() {
+                    None
+                } else {
+                    Some((zone.to_string(), ZoneDevice::parse(device)?))
+                }
+            })
+            .collect()
+    }
+
+    /// Every configured dCS host: the dCS host setting and the dCS devices in the mapping
+    pub fn dcs_hosts(&self) -> Vec<String> {
+        let mut hosts: Vec<String> = self.zone_device_map().into_iter()
+            .filter_map(|(_, device)| device.dcs_host().map(|host| host.to_string()))
+            .collect();
+        if !self.dcs_host.trim().is_empty() {
+            hosts.push(self.dcs_host.trim().to_string());
+        }
+        hosts.sort();
+        hosts.dedup();
+        hosts
+    }
+
+    /// Device for a zone: a mapping for the zone name first, then for one of its outputs,
+    /// then the dCS host for "dCS Vivaldi" zones. None when there is no device (or it is mapped to "none")
+    pub fn device_for_zone(&self, zone: &Zone) -> Option<ZoneDevice> {
+        let map = self.zone_device_map();
+        let mapped = |name: &str| map.iter()
+            .find(|(mapped_name, _)| mapped_name.eq_ignore_ascii_case(name))
+            .map(|(_, device)| device.clone());
+
+        let device = mapped(&zone.display_name)
+            .or_else(|| zone.outputs.iter().find_map(|output| mapped(&output.display_name)));
+        match device {
+            Some(ZoneDevice::None) => None,
+            Some(device) => Some(device),
+            None if zone.display_name.starts_with(DCS_ZONE_PREFIX) && !self.dcs_host.is_empty() => {
+                Some(ZoneDevice::Dcs(self.dcs_host.clone()))
+            }
+            None => None,
+        }
+    }
+
+    /// Values that correspond to config file keys, as ("section.key", value)
+    /// Used by `config show` to report settings changed from Roon
+    pub fn config_entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("roon.core".to_string(), self.core_address.clone()),
+            ("server.port".to_string(), self.server_port.to_string()),
+            ("dcs.host".to_string(), self.dcs_host.clone()),
+            ("display.album_art_size".to_string(), self.album_art_size.to_string()),
+            ("display.artist_image_interval_secs".to_string(), self.artist_image_interval_secs.to_string()),
+        ];
+        for (zone, device) in self.zone_device_map() {
+            entries.push((format!("zones.{}", zone), device.to_string()));
+        }
+        entries
+    }
+
+    /// Check settings entered in Roon, returning an error message for the first invalid one
+    fn validate(&self) -> Option<String> {
+        if self.server_port == 0 {
+            return Some("Server port must be between 1 and 65535".to_string());
+        }
+        if self.album_art_size < 50 || self.album_art_size > 2000 {
+            return Some("Album art size must be between 50 and 2000".to_string());
+        }
+        let entries = self.zone_devices.split([',', ';', '\n'])
+            .filter(|entry| !entry.trim().is_empty())
+            .count();
+        if entries != self.zone_device_map().len() {
+            return Some("Zone devices must be entries like: Living Room=dcs:dcs-vivaldi.local, Den=upnp:http://host:port/description.xml, Kitchen=none".to_string());
+        }
+        None
+    }
+}
+
+/// Build the settings layout shown in Roon Settings > Extensions
+pub fn make_layout(settings: ExtensionSettings) -> Layout<ExtensionSettings> {
+    let error = settings.validate();
+
+    let widgets = vec![
+        Widget::Textbox(Textbox {
+            title: "Core address",
+            subtitle: Some("host:port to connect without discovery (empty uses discovery), applied on reconnect".to_string()),
+            setting: "core_address",
+        }),
+        Widget::Textbox(Textbox {
+            title: "dCS host",
+            subtitle: Some("Hostname or IP of the dCS device".to_string()),
+            setting: "dcs_host",
+        }),
+        Widget::Textbox(Textbox {
+            title: "Zone devices",
+            subtitle: Some("Zone or output=device pairs, comma separated (e.g. Living Room=dcs:dcs-vivaldi.local, Den=upnp:http://..., Kitchen=none)".to_string()),
+            setting: "zone_devices",
+        }),
+        Widget::Integer(Integer {
+            title: "Server port",
+            subtitle: Some("Web display port, applied on restart".to_string()),
+            min: 1,
+            max: 65535,
+            setting: "server_port",
+        }),
+        Widget::Integer(Integer {
+            title: "Artist image interval",
+            subtitle: Some("Seconds between artist images (0 disables rotation)".to_string()),
+            min: 0,
+            max: 600,
+            setting: "artist_image_interval_secs",
+        }),
+        Widget::Integer(Integer {
+            title: "Album art size",
+            subtitle: Some("Pixels".to_string()),
+            min: 50,
+            max: 2000,
+            setting: "album_art_size",
+        }),
+    ];
+
+    if let Some(error) = &error {
+        log::warn!("Invalid extension settings: {}", error);
+    }
+
+    Layout {
+        settings,
+        widgets,
+        has_error: error.is_some(),
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use crate::history::{self, PlayRecord};
+
+/// Days covered when no start date is given
+pub const DEFAULT_RANGE_DAYS: i64 = 7;
+
+/// Entries in each top list when no limit is given
+pub const DEFAULT_TOP_COUNT: usize = 10;
+
+/// Listening statistics over a date range, computed from the play history log
+#[derive(Clone, Debug, Serialize)]
+pub struct ListeningStats {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub zone: Option<String>,
+    pub plays: usize,
+    pub listening_hours: f64,
+    pub top_artists: Vec<TopEntry>,
+    pub top_albums: Vec<TopEntry>,
+    pub top_tracks: Vec<TopEntry>,
+    pub zones: Vec<ZoneStats>,
+    pub days: Vec<DayStats>,
+    pub hours: Vec<u64>, // Minutes listened by local hour of day (24 entries)
+    pub heatmap: Vec<Vec<u64>>, // Minutes listened by weekday (Monday first) and local hour
+}
+
+/// An artist, album or track with how often and how long it was played
+#[derive(Clone, Debug, Serialize)]
+pub struct TopEntry {
+    pub name: String,
+    pub artist: Option<String>, // For albums and tracks
+    pub plays: usize,
+    pub minutes: u64,
+}
+
+/// Listening time in one zone
+#[derive(Clone, Debug, Serialize)]
+pub struct ZoneStats {
+    pub zone_name: String,
+    pub plays: usize,
+    pub hours: f64,
+}
+
+/// Listening time on one local calendar day
+#[derive(Clone, Debug, Serialize)]
+pub struct DayStats {
+    pub date: String, // YYYY-MM-DD
+    pub plays: usize,
+    pub minutes: u64,
+}
+
+/// Running totals for one key
+#[derive(Default)]
+struct Tally {
+    plays: usize,
+    secs: u64,
+}
+
+impl Tally {
+    fn add(&mut self, record: &PlayRecord) {
+        self.plays += 1;
+        self.secs += record.played_secs;
+    }
+}
+
+/// Compute statistics for plays started in [since, until), optionally in one zone (id or name)
+/// `since` defaults to DEFAULT_RANGE_DAYS ago and `until` to now
+pub fn compute(
+    zone: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: usize,
+) -> Result<ListeningStats, String> {
+    let until = until.unwrap_or_else(Utc::now);
+    let since = since.unwrap_or_else(|| until - chrono::Duration::days(DEFAULT_RANGE_DAYS));
+    let records: Vec<PlayRecord> = history::read_history(zone, Some(since))?
+        .into_iter()
+        .filter(|record| record.started_at < until)
+        .collect();
+
+    let mut artists: HashMap<String, Tally> = HashMap::new();
+    let mut albums: HashMap<(String, Option<String>), Tally> = HashMap::new();
+    let mut tracks: HashMap<(String, Option<String>), Tally> = HashMap::new();
+    let mut zones: HashMap<String, Tally> = HashMap::new();
+    let mut days: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut hour_secs = vec![0u64; 24];
+    let mut heatmap_secs = vec![vec![0u64; 24]; 7];
+    let mut total_secs = 0;
+
+    for record in &records {
+        total_secs += record.played_secs;
+
+        if let Some(artist) = &record.artist {
+            artists.entry(artist.clone()).or_default().add(record);
+        }
+        if let Some(album) = &record.album {
+            albums.entry((album.clone(), record.artist.clone())).or_default().add(record);
+        }
+        tracks.entry((record.track.clone(), record.artist.clone())).or_default().add(record);
+        zones.entry(record.zone_name.clone()).or_default().add(record);
+
+        // Plays count toward the local day and hour they started in
+        let started = record.started_at.with_timezone(&Local);
+        days.entry(started.format("%Y-%m-%d").to_string()).or_default().add(record);
+        let hour = started.hour() as usize;
+        hour_secs[hour] += record.played_secs;
+        heatmap_secs[started.weekday().num_days_from_monday() as usize][hour] += record.played_secs;
+    }
+
+    let mut zone_stats: Vec<ZoneStats> = zones.into_iter()
+        .map(|(zone_name, tally)| ZoneStats { zone_name, plays: tally.plays, hours: secs_to_hours(tally.secs) })
+        .collect();
+    zone_stats.sort_by(|a, b| b.hours.total_cmp(&a.hours));
+
+    Ok(ListeningStats {
+        since,
+        until,
+        zone: zone.map(|zone| zone.to_string()),
+        plays: records.len(),
+        listening_hours: secs_to_hours(total_secs),
+        top_artists: top(artists.into_iter().map(|(name, tally)| ((name, None), tally)), limit),
+        top_albums: top(albums.into_iter(), limit),
+        top_tracks: top(tracks.into_iter(), limit),
+        zones: zone_stats,
+        days: days.into_iter()
+            .map(|(date, tally)| DayStats { date, plays: tally.plays, minutes: tally.secs / 60 })
+            .collect(),
+        hours: hour_secs.into_iter().map(|secs| secs / 60).collect(),
+        heatmap: heatmap_secs.into_iter()
+            .map(|day| day.into_iter().map(|secs| secs / 60).collect())
+            .collect(),
+    })
+}
+
+/// Most played first, ties broken by listening time, then name
+fn top(tallies: impl Iterator<Item = ((String, Option<String>), Tally)>, limit: usize) -> Vec<TopEntry> {
+    let mut entries: Vec<TopEntry> = tallies
+        .map(|((name, artist), tally)| TopEntry { name, artist, plays: tally.plays, minutes: tally.secs / 60 })
+        .collect();
+    entries.sort_by(|a, b| b.plays.cmp(&a.plays).then(b.minutes.cmp(&a.minutes)).then(a.name.cmp(&b.name)));
+    entries.truncate(limit);
+    entries
+}
+
+fn secs_to_hours(secs: u64) -> f64 {
+    (secs as f64 / 360.0).round() / 10.0
+}
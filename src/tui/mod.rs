@@ -20,12 +20,7 @@ use simplelog::{Config as LogConfig, LevelFilter, SharedLogger};
 
 /// Get the path to the history file
 fn get_history_file_path() -> Option<PathBuf> {
-    if let Some(home_dir) = dirs::home_dir() {
-        let history_path = home_dir.join(".roon-rd_history");
-        Some(history_path)
-    } else {
-        None
-    }
+    Some(crate::config::history_path())
 }
 
 /// Load command history from file
@@ -1471,12 +1466,21 @@ where
                                 format!("[{}] zones_changed (raw JSON not available)", Local::now().format("%H:%M:%S"))
                             }
                         }
-                        crate::roon::WsMessage::ConnectionChanged { connected } => {
-                            format!("[{}] connection_changed: {:#?}", Local::now().format("%H:%M:%S"), connected)
+                        crate::roon::WsMessage::ConnectionChanged { state, .. } => {
+                            format!("[{}] connection_changed: {:#?}", Local::now().format("%H:%M:%S"), state)
                         }
                         crate::roon::WsMessage::QueueChanged { zone_id } => {
                             format!("[{}] queue_changed: {:#?}", Local::now().format("%H:%M:%S"), zone_id)
                         }
+                        crate::roon::WsMessage::OutputsChanged { outputs } => {
+                            format!("[{}] outputs_changed: {:#?}", Local::now().format("%H:%M:%S"), outputs)
+                        }
+                        crate::roon::WsMessage::DeviceChanged { state } => {
+                            format!("[{}] device_changed: {:#?}", Local::now().format("%H:%M:%S"), state)
+                        }
+                        crate::roon::WsMessage::ArtistImageChanged { zone_id, image_key, index, count } => {
+                            format!("[{}] artist_image_changed: zone={} image_key={} ({}/{})", Local::now().format("%H:%M:%S"), zone_id, image_key, index + 1, count)
+                        }
                         crate::roon::WsMessage::SeekUpdated { .. } => unreachable!(),
                     }
                 } else {
@@ -1489,12 +1493,21 @@ where
                                 format!("[{}] zones_changed (raw JSON not available)", Local::now().format("%H:%M:%S"))
                             }
                         }
-                        crate::roon::WsMessage::ConnectionChanged { connected } => {
-                            format!("[{}] connection_changed: {}", Local::now().format("%H:%M:%S"), connected)
+                        crate::roon::WsMessage::ConnectionChanged { state, .. } => {
+                            format!("[{}] connection_changed: {}", Local::now().format("%H:%M:%S"), state)
                         }
                         crate::roon::WsMessage::QueueChanged { zone_id } => {
                             format!("[{}] queue_changed: zone={}", Local::now().format("%H:%M:%S"), zone_id)
                         }
+                        crate::roon::WsMessage::OutputsChanged { outputs } => {
+                            format!("[{}] outputs_changed: {} outputs", Local::now().format("%H:%M:%S"), outputs.len())
+                        }
+                        crate::roon::WsMessage::DeviceChanged { state } => {
+                            format!("[{}] device_changed: {} format={}", Local::now().format("%H:%M:%S"), state.host, state.format.as_deref().unwrap_or("-"))
+                        }
+                        crate::roon::WsMessage::ArtistImageChanged { zone_id, index, count, .. } => {
+                            format!("[{}] artist_image_changed: zone={} ({}/{})", Local::now().format("%H:%M:%S"), zone_id, index + 1, count)
+                        }
                         crate::roon::WsMessage::SeekUpdated { .. } => unreachable!(),
                     }
                 };
@@ -0,0 +1,169 @@
+use roon_api::transport::Zone;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// What we last saw of a zone that was given a stable key
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ZoneKeyRecord {
+    key: String,
+    display_name: String,
+    output_ids: Vec<String>,   // Sorted
+    output_names: Vec<String>, // Sorted
+    zone_id: String,           // Roon zone id when last seen
+}
+
+/// Stable roon-rd zone keys that survive Core reinstalls and regrouping
+/// Roon zone ids change in both cases, so zones are matched to their key by output ids,
+/// then output names, then display name; the mapping is kept in the state directory
+#[derive(Clone)]
+pub struct ZoneKeys {
+    records: Arc<RwLock<Vec<ZoneKeyRecord>>>,
+    current: Arc<RwLock<HashMap<String, String>>>, // zone_id -> key, for zones present now
+    generation: Arc<AtomicU64>, // Bumped on each change to the records
+    written: Arc<Mutex<u64>>,   // Generation last saved, so a slow save can't overwrite a newer one
+}
+
+impl ZoneKeys {
+    /// Load the saved mapping (empty if there is none yet)
+    pub fn load() -> Self {
+        let path = crate::config::zone_keys_path();
+        let records = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid zone keys file {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        ZoneKeys {
+            records: Arc::new(RwLock::new(records)),
+            current: Arc::new(RwLock::new(HashMap::new())),
+            generation: Arc::new(AtomicU64::new(0)),
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Find (or create) the key for a zone from Roon, saving the mapping if it changed
+    pub async fn update(&self, zone: &Zone) -> String {
+        let mut output_ids: Vec<String> = zone.outputs.iter().map(|o| o.output_id.clone()).collect();
+        output_ids.sort();
+        let mut output_names: Vec<String> = zone.outputs.iter().map(|o| o.display_name.clone()).collect();
+        output_names.sort();
+
+        let mut records = self.records.write().await;
+        let mut current = self.current.write().await;
+
+        // Keys held by other zones right now can't be reused
+        let taken: Vec<&String> = current.iter()
+            .filter(|(zone_id, _)| **zone_id != zone.zone_id)
+            .map(|(_, key)| key)
+            .collect();
+        let free = |record: &ZoneKeyRecord| !taken.contains(&&record.key);
+
+        let index = records.iter().position(|r| free(r) && r.zone_id == zone.zone_id)
+            .or_else(|| records.iter().position(|r| free(r) && !output_ids.is_empty() && r.output_ids == output_ids))
+            .or_else(|| records.iter().position(|r| free(r) && !output_names.is_empty() && r.output_names == output_names))
+            .or_else(|| records.iter().position(|r| free(r) && r.display_name.eq_ignore_ascii_case(&zone.display_name)));
+
+        let record = ZoneKeyRecord {
+            key: match index {
+                Some(index) => records[index].key.clone(),
+                None => unique_key(&records, &zone.display_name),
+            },
+            display_name: zone.display_name.clone(),
+            output_ids,
+            output_names,
+            zone_id: zone.zone_id.clone(),
+        };
+        let key = record.key.clone();
+
+        let changed = match index {
+            Some(index) if records[index] == record => false,
+            Some(index) => {
+                records[index] = record;
+                true
+            }
+            None => {
+                log::info!("New zone key '{}' for {}", key, zone.display_name);
+                records.push(record);
+                true
+            }
+        };
+        // Serialized under the lock, written after releasing it
+        let save = if changed {
+            let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+            match serde_json::to_string_pretty(&*records) {
+                Ok(json) => Some((json, generation)),
+                Err(e) => {
+                    log::error!("Failed to serialize zone keys: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        current.insert(zone.zone_id.clone(), key.clone());
+        drop(current);
+        drop(records);
+
+        if let Some((json, generation)) = save {
+            self.save(json, generation).await;
+        }
+        key
+    }
+
+    /// Forget a zone that went away (its key is kept for when it comes back)
+    pub async fn remove_zone(&self, zone_id: &str) {
+        self.current.write().await.remove(zone_id);
+    }
+
+    /// Forget all current zones (e.g. when the Core connection is lost)
+    pub async fn clear(&self) {
+        self.current.write().await.clear();
+    }
+
+    /// zone_id -> key for every current zone
+    pub async fn snapshot(&self) -> HashMap<String, String> {
+        self.current.read().await.clone()
+    }
+
+    /// Write the records serialized at `generation`, unless a newer generation was written already
+    async fn save(&self, json: String, generation: u64) {
+        let mut written = self.written.lock().await;
+        if *written >= generation {
+            return;
+        }
+        let path = crate::config::zone_keys_path();
+        match tokio::fs::write(&path, json).await {
+            Ok(()) => *written = generation,
+            Err(e) => log::error!("Failed to save zone keys to {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Key made from a display name ("Living Room" -> "living-room"), numbered if already used
+fn unique_key(records: &[ZoneKeyRecord], display_name: &str) -> String {
+    let mut base = String::new();
+    for c in display_name.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            base.push(c);
+        } else if !base.is_empty() && !base.ends_with('-') {
+            base.push('-');
+        }
+    }
+    let base = base.trim_end_matches('-');
+    let base = if base.is_empty() { "zone" } else { base };
+
+    let mut key = base.to_string();
+    let mut n = 2;
+    while records.iter().any(|r| r.key == key) {
+        key = format!("{}-{}", base, n);
+        n += 1;
+    }
+    key
+}